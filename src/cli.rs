@@ -0,0 +1,254 @@
+//! Command line arguments
+//!
+//! Without any argument the window is opened, exports run headless.
+
+use crate::consts::{DEFAULT_FILL_DENSITY, DEFAULT_HISTORY_BUDGET, DEFAULT_STEP_COUNT, DEFAULT_WINDOW_SIZE};
+use crate::game::{GridRect, ImportOptions, SvgOptions, Theme};
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: pistony [OPTIONS]
+
+Without export, open the window.
+
+Board options:
+  --load <FILE>             Pattern (RLE) or image (PNG, PBM, PGM, PPM) to start
+                            from, exports default to the last autosave
+  --threshold <0-1>         Image pixels darker than it are alive, defaults to 0.5
+  --dither                  Dither images instead of thresholding them
+  --scale <FACTOR>          Cells per image pixel, defaults to 1
+
+Images and patterns dropped on the window are imported with the same options.
+
+Window options:
+  --patterns <DIR>          Directory of .rle and .cells files to browse with B,
+                            defaults to $PISTONY_PATTERNS
+  --fill-density <0-1>      Alive cells when filling the selection randomly,
+                            defaults to 0.35
+  --history <MB>            Memory kept for undoing, defaults to 64
+  --step <N>                Generations run by LShift+Period, defaults to 10
+  --no-inertia              Stop the view as soon as a pan is released
+
+Window keys:
+  Space                     Play or pause, only drawing and shapes edit a playing board
+  Arrows, S, X, LShift      Move, zoom in, zoom out, faster
+  Mouse wheel               Zoom around the cursor, doubling or halving the cells
+  Middle drag, Space+drag   Pan the view
+  A, LShift+A               Frame the whole pattern, the selection
+  F10                       Show the minimap, click or drag on it to move the view
+  Y, LShift+Y               Follow the selected object, or the whole pattern,
+                            keeping it centered or framed
+  O, P                      Slower, faster steps, beyond 1000/s as fast as possible
+  LShift+O, LShift+P        Halve, double the generations of a step
+  U                         Hyperspeed, doubles the generations of a step every 2s
+  C                         Clear the board
+  T                         Next color theme
+  L, Tab, LShift+Tab        Pattern library, next and previous pattern
+  B                         Browse the pattern directory
+  D, M                      Draw tool, selection tool
+  Left click, right click   Draw from a dead cell or erase from a live one, erase,
+                            holding LShift always draws, LAlt always erases
+  [, ]                      Previous, next brush
+  W, Q, E                   Line, rectangle, ellipse tools, Q and E again to fill,
+                            drag with the left button to draw, the right to erase
+  F                         Flood fill tool, switches the region under the cursor
+  Ctrl+C, Ctrl+X, Ctrl+V    Copy, cut, paste the selection
+  Delete, Ctrl+S            Delete, save the selection
+  Ctrl+Z, Ctrl+Y            Undo, redo an edit or a whole run
+  Comma, Period             Previous, next generation, or drag the bar at the bottom
+  LShift+Comma, LShift+Period
+                            Go back, run N generations, see --step
+  G                         Type a generation to run until, then Enter
+  Ctrl+G                    Type the x,y of a cell to go to, then Enter
+  Ctrl+0 to Ctrl+9, 0 to 9  Save the view as a bookmark, fly back to it
+  R, LShift+R, Ctrl+R       Rotate the selection by 90°, 270°, 180°
+  H, V, J                   Flip the selection horizontally, vertically, transpose it
+  I, N, K                   Invert, fill randomly, clear around the selection
+  Arrows                    Nudge the selection while there is one
+  Escape                    Cancel the placement or the selection
+  F11                       Save the selection, or else the view, as SVG
+  F12                       Save the view as PNG
+
+Headless exports:
+  --screenshot <FILE.png>   Render the default view of the board to a PNG
+  --gif <FILE.gif>          Render generations as an animated GIF
+  --svg <FILE.svg>          Render the default view of the board as vectors
+
+Export options:
+  --size <WIDTH>x<HEIGHT>   Size of the rendered view, in pixels
+  --palette <PALETTE>       dark, light, matrix or <BACKGROUND>,<CELL> as hex RGB
+  --cell-size <PX>          Size of a cell in pixels for GIF and SVG regions,
+                            defaults to 4
+
+GIF options:
+  --from <N>                First generation, defaults to 0
+  --to <M>                  Last generation, defaults to 100
+  --delay <MS>              Delay between frames in milliseconds, defaults to 100
+  --crop <view|bounds>      Default camera view or bounding box of the pattern
+                            over every exported generation, defaults to bounds
+
+SVG options:
+  --region <X>,<Y>,<W>,<H>  Export these cells instead of the default view
+  --grid-lines              Draw the lines between cells
+  --labels                  Write the coordinates along the edges
+";
+
+pub enum Command {
+    Gui(GuiOptions),
+    Help,
+    Screenshot(ExportOptions),
+    Gif(ExportOptions, GifOptions),
+    Svg(ExportOptions, SvgOptions, Option<GridRect>),
+}
+
+pub struct GuiOptions {
+    pub pattern: Option<PathBuf>,
+    pub image: ImportOptions,
+    pub pattern_dir: Option<PathBuf>,
+    pub fill_density: f64,
+    /// In megabytes
+    pub history_budget: usize,
+    pub step_count: u64,
+    /// The view keeps moving after a pan is released
+    pub inertia: bool,
+}
+
+pub struct ExportOptions {
+    pub output: PathBuf,
+    pub pattern: Option<PathBuf>,
+    pub image: ImportOptions,
+    /// In `ScreenPosition`
+    pub size: [f64; 2],
+    pub theme: Theme,
+    pub cell_size: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Crop {
+    View,
+    Bounds,
+}
+
+pub struct GifOptions {
+    pub from: usize,
+    pub to: usize,
+    pub delay_ms: u32,
+    pub crop: Crop,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions { from: 0, to: 100, delay_ms: 100, crop: Crop::Bounds }
+    }
+}
+
+enum Output {
+    Screenshot(PathBuf),
+    Gif(PathBuf),
+    Svg(PathBuf),
+}
+
+fn parse_number<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value {:?} for {}", value, arg))
+}
+
+/// ## Errors
+/// If an option is unknown or its value is missing or invalid
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let mut output = None;
+    let mut pattern = None;
+    let mut image = ImportOptions::default();
+    let mut size = DEFAULT_WINDOW_SIZE;
+    let mut theme = Theme::default();
+    let mut cell_size = 4;
+    let mut gif = GifOptions::default();
+    let mut svg = SvgOptions::default();
+    let mut region = None;
+    let mut fill_density = DEFAULT_FILL_DENSITY;
+    let mut history_budget = DEFAULT_HISTORY_BUDGET;
+    let mut step_count = DEFAULT_STEP_COUNT;
+    let mut inertia = true;
+    let mut pattern_dir = std::env::var_os("PISTONY_PATTERNS").map(PathBuf::from);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--screenshot" => output = Some(Output::Screenshot(PathBuf::from(value()?))),
+            "--gif" => output = Some(Output::Gif(PathBuf::from(value()?))),
+            "--svg" => output = Some(Output::Svg(PathBuf::from(value()?))),
+            "--load" => pattern = Some(PathBuf::from(value()?)),
+            "--threshold" => image.threshold = parse_number(&arg, &value()?)?,
+            "--dither" => image.dither = true,
+            "--scale" => image.scale = parse_number(&arg, &value()?)?,
+            "--size" => size = parse_size(&value()?)?,
+            "--palette" => theme = Theme::parse(&value()?)?,
+            "--from" => gif.from = parse_number(&arg, &value()?)?,
+            "--to" => gif.to = parse_number(&arg, &value()?)?,
+            "--delay" => gif.delay_ms = parse_number(&arg, &value()?)?,
+            "--cell-size" => cell_size = parse_number(&arg, &value()?)?,
+            "--crop" => gif.crop = match value()?.as_str() {
+                "view" => Crop::View,
+                "bounds" => Crop::Bounds,
+                other => return Err(format!("Invalid crop {:?}, expected view or bounds", other))
+            },
+            "--fill-density" => fill_density = parse_number(&arg, &value()?)?,
+            "--history" => history_budget = parse_number(&arg, &value()?)?,
+            "--step" => step_count = parse_number(&arg, &value()?)?,
+            "--no-inertia" => inertia = false,
+            "--patterns" => pattern_dir = Some(PathBuf::from(value()?)),
+            "--region" => region = Some(parse_region(&value()?)?),
+            "--grid-lines" => svg.grid_lines = true,
+            "--labels" => svg.labels = true,
+            _ => return Err(format!("Unknown argument {:?}", arg))
+        }
+    }
+
+    if gif.from > gif.to {
+        return Err(format!("--from {} is after --to {}", gif.from, gif.to));
+    }
+    if image.scale.is_nan() || image.scale <= 0.0 {
+        return Err(String::from("--scale must be positive"));
+    }
+    if !(0.0..=1.0).contains(&fill_density) {
+        return Err(String::from("--fill-density must be between 0 and 1"));
+    }
+    if cell_size == 0 {
+        return Err(String::from("--cell-size must be at least 1"));
+    }
+
+    let export = |output, pattern| ExportOptions { output, pattern, image, size, theme, cell_size };
+    Ok(match output {
+        Some(Output::Screenshot(output)) => Command::Screenshot(export(output, pattern)),
+        Some(Output::Gif(output)) => Command::Gif(export(output, pattern), gif),
+        Some(Output::Svg(output)) => Command::Svg(export(output, pattern), svg, region),
+        None => Command::Gui(GuiOptions {
+            pattern, image, pattern_dir, fill_density, history_budget, step_count, inertia
+        })
+    })
+}
+
+fn parse_size(value: &str) -> Result<[f64; 2], String> {
+    let invalid = || format!("Invalid size {:?}, expected <WIDTH>x<HEIGHT>", value);
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok([width as f64, height as f64])
+}
+
+fn parse_region(value: &str) -> Result<GridRect, String> {
+    let invalid = || format!("Invalid region {:?}, expected <X>,<Y>,<WIDTH>,<HEIGHT>", value);
+    let numbers = value.split(',')
+        .map(|number| number.trim().parse::<usize>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match numbers[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(GridRect { x, y, width, height }),
+        _ => Err(invalid())
+    }
+}
@@ -4,9 +4,13 @@ pub const OPEN_GL: OpenGL = OpenGL::V4_5;
 
 pub const BG_COLOR: [f32; 4] = [0.08, 0.08, 0.08, 1.0];
 pub const CELL_COLOR: [f32; 4] = [0.95, 0.95, 0.95, 1.0];
+/// Opacity of a pattern that is not placed yet
+pub const GHOST_ALPHA: f32 = 0.4;
 pub const ZOOM_SPEED: f64 = 1.0;
 pub const SUPER_ZOOM_SPEED: f64 = 1.5;
 
+pub const WINDOW_TITLE: &str = "Conway's Game Of Life";
+
 /// In `ScreenPosition`
 pub const DEFAULT_WINDOW_SIZE: [f64; 2] = [780.0, 360.0];
 
@@ -19,11 +23,97 @@ pub const WORLD_HEIGHT: f64 = 500.0;
 /// Overriden by `WORLD_WIDTH` if the latter is smaller
 pub const DEFAULT_CAMERA_LENGTH: f64 = 100.0;
 
-pub const DT_BEFORE_UPDATE: f64 = 1.0; // in seconds
 /// Camera moving speed
 pub const SPEED: f64 = 45.0;
 /// Camera moving speed
 pub const SUPER_SPEED: f64 = 200.0;
 
 pub const GRID_WIDTH: usize = WORLD_WIDTH as usize;
-pub const GRID_HEIGHT: usize = WORLD_HEIGHT as usize;
\ No newline at end of file
+pub const GRID_HEIGHT: usize = WORLD_HEIGHT as usize;
+/// Seconds between two autosaves
+pub const AUTOSAVE_INTERVAL: f64 = 30.0;
+/// Number of rotated autosave snapshots kept on disk
+pub const AUTOSAVE_SNAPSHOTS: usize = 5;
+/// Name of the directory holding autosaves and other local state
+pub const STATE_DIR_NAME: &str = "pistony";
+
+/// Seconds between two scans of the pattern directory
+pub const PATTERN_SCAN_INTERVAL: f64 = 2.0;
+/// Largest pattern read from a file, in cells, larger headers are rejected before allocating
+pub const MAX_PATTERN_CELLS: usize = 1 << 24;
+/// Largest side of a pattern thumbnail, in cells
+pub const THUMBNAIL_CELLS: usize = 48;
+/// Side of a thumbnail in the pattern browser, in `ScreenPosition`
+pub const BROWSER_TILE: f64 = 96.0;
+/// Space around thumbnails in the pattern browser, in `ScreenPosition`
+pub const BROWSER_TILE_MARGIN: f64 = 8.0;
+
+/// Proportion of alive cells when filling the selection randomly
+pub const DEFAULT_FILL_DENSITY: f64 = 0.35;
+
+/// Memory kept for undoing, in megabytes
+pub const DEFAULT_HISTORY_BUDGET: usize = 64;
+/// Seconds a message stays in the title bar
+pub const MESSAGE_DURATION: f64 = 3.0;
+
+/// Generations between two keyframes of the timeline
+pub const KEYFRAME_INTERVAL: u64 = 8;
+/// Memory kept for the timeline, in megabytes
+pub const TIMELINE_BUDGET: usize = 64;
+/// Height of the timeline bar at the bottom of the window
+pub const SCRUBBER_HEIGHT: f64 = 10.0;
+/// Generations run by LShift+Period
+pub const DEFAULT_STEP_COUNT: u64 = 10;
+/// Seconds of simulation per frame, to stay responsive
+pub const FRAME_WORK_BUDGET: f64 = 0.02;
+
+/// Steps per second at launch
+pub const DEFAULT_RATE: f64 = 5.0;
+pub const MIN_RATE: f64 = 0.25;
+/// Beyond it the game runs as fast as possible
+pub const MAX_RATE: f64 = 1000.0;
+/// Rate multiplier of P and O, four presses double the rate
+pub const RATE_FACTOR: f64 = 1.189_207_115;
+/// Each step runs at most 2^`MAX_STEP_EXPONENT` generations
+pub const MAX_STEP_EXPONENT: u32 = 10;
+/// Seconds of play before hyperspeed increases the step exponent
+pub const HYPERSPEED_INTERVAL: f64 = 2.0;
+
+/// Smallest cell of the wheel zoom, as the log2 of its size in pixels
+pub const MIN_ZOOM_LEVEL: i32 = -2;
+/// Largest cell of the wheel zoom, as the log2 of its size in pixels
+pub const MAX_ZOOM_LEVEL: i32 = 6;
+/// How fast the wheel zoom reaches its level, the higher the faster
+pub const ZOOM_SMOOTHNESS: f64 = 12.0;
+
+/// How fast a released pan slows down, the higher the sooner it stops
+pub const PAN_FRICTION: f64 = 4.0;
+/// In `WorldPosition` per second, slower pans stop
+pub const MIN_PAN_VELOCITY: f64 = 0.5;
+/// Seconds of cursor motion the pan velocity is averaged over
+pub const PAN_VELOCITY_WINDOW: f64 = 0.05;
+/// Fastest released pan, in view widths per second
+pub const MAX_PAN_SPEED: f64 = 4.0;
+/// In `ScreenPosition`, dragging closer to the edges of the window scrolls
+pub const EDGE_SCROLL_MARGIN: f64 = 16.0;
+
+/// How fast the camera flies to a framing, the higher the faster
+pub const CAMERA_SMOOTHNESS: f64 = 6.0;
+/// Space left around a framed region, relative to its size
+pub const FIT_MARGIN: f64 = 0.1;
+/// In `WorldPosition`, framing never zooms closer
+pub const MIN_FIT_WIDTH: f64 = 16.0;
+/// Cells around a followed object where it is looked for at the next generation
+pub const FOLLOW_MARGIN: usize = 2;
+/// Seconds to fly to a bookmark or a coordinate
+pub const FLY_DURATION: f64 = 0.8;
+
+/// Largest side of the minimap, in `ScreenPosition`
+pub const MINIMAP_SIZE: f64 = 160.0;
+/// Space between the minimap and the edges of the window
+pub const MINIMAP_MARGIN: f64 = 8.0;
+/// Blocks of cells along the longest side of the minimap
+pub const MINIMAP_BLOCKS: usize = 100;
+
+/// Below this size in pixels, cells are drawn aggregated in blocks
+pub const LOD_CELL_PIXELS: f64 = 1.0;
@@ -2,119 +2,490 @@ mod grid;
 mod game_state;
 mod positions;
 mod camera;
+mod rle;
+mod autosave;
+mod raster;
+mod theme;
+mod animation;
+mod svg;
+mod bitmap;
+mod files;
+mod library;
+mod cells;
+mod pattern_index;
+mod browser;
+mod tool;
+mod selection;
+mod random;
+mod brush;
+mod shapes;
+mod history;
+mod timeline;
+mod scheduler;
+mod worker;
+mod bookmarks;
+mod minimap;
+mod lod;
+mod runs;
+pub mod export;
 
 use grid::GameGrid;
 use game_state::GameState;
 use positions::*;
 use camera::Camera;
+pub use autosave::Autosave;
+pub use theme::Theme;
+pub use svg::SvgOptions;
+pub use positions::GridRect;
+pub use bitmap::ImportOptions;
+use theme::THEMES;
+use library::LIBRARY;
+use pattern_index::PatternIndex;
+use browser::Browser;
+use tool::Tool;
+use random::Random;
+use brush::BRUSHES;
+use history::History;
+use timeline::Timeline;
+use worker::{Edit, Simulation, Update};
+use bookmarks::{Bookmarks, BOOKMARK_COUNT};
 use crate::consts::*;
 
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use opengl_graphics::GlGraphics;
 use piston::{Button, Key, MouseButton, RenderArgs, ResizeArgs, UpdateArgs};
 
 
-
-
-#[derive(Clone, Copy)]
-enum Speed {
-    InsanelyFast,
-    SuperFast,
-    VeryFast,
-    Fast,
-    Normal,
-    Slow,
-    VerySlow,
-    SuperSlow
-}
-
-impl From<Speed> for f64 {
-    fn from(value: Speed) -> Self {
-        match value {
-            Speed::InsanelyFast => 100.0, // 100 updates per second
-            Speed::SuperFast => 50.0, // 50 updates per second
-            Speed::VeryFast => 20.0, // 20 updates per second
-            Speed::Fast => 10.0, // 10 updates per second
-            Speed::Normal => 5.0, // 5 updates per second
-            Speed::Slow => 2.0, // 2 updates every second
-            Speed::VerySlow => 0.5, // 2 secs for an update
-            Speed::SuperSlow => 0.25 // 4 secs for an update
-        }
-    }
-}
-
-
 pub struct Game {
     gl: GlGraphics,
     camera: Camera,
     board: GameGrid,
     state: GameState,
-    total_dt: f64,
-    speed: Speed,
+    /// Runs the generations while the game plays
+    simulation: Simulation,
+    /// Steps per second, infinite meaning as many as a frame can fit
+    rate: f64,
+    /// Each step runs 2^`step_exponent` generations
+    step_exponent: u32,
+    /// Increase the step exponent as the game plays
+    hyperspeed: bool,
+    /// Seconds played since the step exponent last increased in hyperspeed
+    hyperspeed_time: f64,
     mouse_coords: Option<[f64; 2]>,
     pressed_buttons: HashSet<Button>,
-    changed_tiles: HashSet<GridPosition>,
+    /// Keys pressed while Ctrl was held, they stay chords until released
+    chords: HashSet<Key>,
+    stroke: Option<Stroke>,
+    /// Line, rectangle or ellipse being dragged
+    shape: Option<ShapeDrag>,
+    /// Cached until what it depends on changes
+    preview: Option<Preview>,
+    /// Index in `BRUSHES`
+    brush: usize,
+    autosave: Autosave,
+    /// Index in `THEMES`
+    theme: usize,
+    /// How images dropped on the window are converted
+    image_options: ImportOptions,
+    /// Pattern following the cursor until it is clicked into place
+    ghost: Option<Ghost>,
+    /// Directory of user patterns, if one is given
+    pattern_index: Option<PatternIndex>,
+    /// Open pattern browser
+    browser: Option<Browser>,
+    tool: Tool,
+    selection: Option<GridRect>,
+    /// Under the selection since it was last nudged or transformed
+    covered: Option<Covered>,
+    /// Corner where the selection started while it is being dragged
+    selection_start: Option<GridPosition>,
+    clipboard: Option<GameGrid>,
+    /// Proportion of alive cells when filling the selection randomly
+    fill_density: f64,
+    random: Random,
+    history: History,
+    /// Board before the edit in progress
+    edit_start: Option<GameGrid>,
+    timeline: Timeline,
+    /// The timeline bar is being dragged
+    scrubbing: bool,
+    /// Generation being reached while staying in pause
+    target_generation: Option<u64>,
+    /// Text typed in the title bar, until Enter
+    prompt: Option<Prompt>,
+    /// Shown in the title bar, with the seconds it has left
+    message: Option<(String, f64)>,
+    /// Generations run by LShift+Period
+    step_count: u64,
+    /// View being dragged
+    pan: Option<Pan>,
+    /// Space was held to pan, releasing it does not switch pause
+    space_panned: bool,
+    /// In `WorldPosition` per second, slowing down after a pan is released
+    camera_velocity: [f64; 2],
+    inertia: bool,
+    follow: Option<Follow>,
+    bookmarks: Bookmarks,
+    show_minimap: bool,
+    /// The view is being dragged on the minimap
+    minimap_dragging: bool,
+}
+
+struct Ghost {
+    pattern: GameGrid,
+    name: String,
+    /// Index in `LIBRARY` when picked from it
+    library_index: Option<usize>,
+    /// Position of the cursor relative to the top left of the pattern, in cells
+    anchor: [f64; 2],
+    /// Dragged from the selection, it is dropped when the button is released
+    moving: bool,
+}
+
+/// Cells that a nudged or transformed selection covers, put back when it moves again
+struct Covered {
+    rect: GridRect,
+    cells: GameGrid,
+    /// Of the board right after the selection was put over them, any other change forgets them
+    revision: u64,
+}
+
+/// Cells being drawn or erased while the button is held
+struct Stroke {
+    /// State painted, decided by the first cell
+    alive: bool,
+    /// Cell under the cursor at the previous mouse move, it can be outside the grid
+    last: [isize; 2],
+}
+
+struct Pan {
+    /// Cursor at the previous move
+    last: ScreenPosition,
+    last_time: Instant,
+    /// In `WorldPosition` per second, averaged over `PAN_VELOCITY_WINDOW`
+    velocity: [f64; 2],
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PromptKind {
+    /// Generation to run until
+    Generation,
+    /// `x,y` of the cell to go to
+    Coordinate,
+    /// Y or N, to restore the session that did not exit properly
+    Restore,
+}
+
+struct Prompt {
+    kind: PromptKind,
+    text: String,
+}
+
+/// Camera tracking the pattern as it moves
+struct Follow {
+    /// Cells of the followed object, the whole pattern if `None`
+    object: Option<GridRect>,
+    /// Zoom to show the whole object, otherwise only keep its centroid at the center
+    frame: bool,
+    /// Generation when the object was last found, `None` before the first time
+    generation: Option<u64>,
+}
+
+/// Everything a preview is computed from
+#[derive(Clone, Copy, PartialEq)]
+struct PreviewKey {
+    tool: Tool,
+    cursor: [isize; 2],
+    /// Start and new state of the dragged shape
+    shape: Option<([isize; 2], bool)>,
+    snap: bool,
+    revision: u64,
+}
+
+/// Cells that the dragged shape or a flood fill at the cursor would paint
+struct Preview {
+    key: PreviewKey,
+    /// Painted cells in their bounding box
+    cells: GameGrid,
+    /// Top left cell of the bounding box, `[x, y]`
+    origin: [usize; 2],
+    /// New state of the painted cells
+    alive: bool,
+}
+
+struct ShapeDrag {
+    start: [isize; 2],
+    /// Drawn with the left button, erased with the right one
+    alive: bool,
+}
+
+impl Ghost {
+    /// Ghost held by its center
+    fn new(pattern: GameGrid, name: String, library_index: Option<usize>) -> Self {
+        let (rows, cols) = pattern.get_shape();
+        Ghost { pattern, name, library_index, anchor: [cols as f64 / 2.0, rows as f64 / 2.0], moving: false }
+    }
 }
 
 impl Game {
-    pub fn new() -> Self {
+    pub fn new(autosave: Autosave) -> Self {
+        let board = GameGrid::new(GRID_HEIGHT, GRID_WIDTH);
         Game {
             gl: GlGraphics::new(OPEN_GL),
             camera: Camera::default(),
-            board: GameGrid::new(GRID_HEIGHT, GRID_WIDTH),
+            timeline: Timeline::new(&board, TIMELINE_BUDGET << 20),
+            board,
             state: GameState::Pause,
-            total_dt: 0.0,
-            speed: Speed::Normal,
+            simulation: Simulation::spawn(),
+            rate: DEFAULT_RATE,
+            step_exponent: 0,
+            hyperspeed: false,
+            hyperspeed_time: 0.0,
             mouse_coords: None,
             pressed_buttons: HashSet::new(),
-            changed_tiles: HashSet::new(),
+            chords: HashSet::new(),
+            stroke: None,
+            shape: None,
+            preview: None,
+            brush: 0,
+            autosave,
+            theme: 0,
+            image_options: ImportOptions::default(),
+            ghost: None,
+            pattern_index: None,
+            browser: None,
+            tool: Tool::Draw,
+            selection: None,
+            covered: None,
+            selection_start: None,
+            clipboard: None,
+            fill_density: DEFAULT_FILL_DENSITY,
+            random: Random::new(),
+            history: History::new(DEFAULT_HISTORY_BUDGET << 20),
+            edit_start: None,
+            scrubbing: false,
+            target_generation: None,
+            prompt: None,
+            message: None,
+            step_count: DEFAULT_STEP_COUNT,
+            pan: None,
+            space_panned: false,
+            camera_velocity: [0.0, 0.0],
+            inertia: true,
+            follow: None,
+            bookmarks: Bookmarks::load(),
+            show_minimap: false,
+            minimap_dragging: false,
+        }
+    }
+
+    /// Ask in the title bar whether to restore the last session
+    pub fn offer_restore(&mut self) {
+        self.prompt = Some(Prompt { kind: PromptKind::Restore, text: String::new() });
+    }
+
+    /// Replace the board by the last autosave, if there is one
+    fn restore_last_session(&mut self) {
+        if let Some(board) = self.autosave.latest() {
+            if board.get_shape() == self.board.get_shape() {
+                self.board = board;
+                self.timeline.rewrite(&self.board);
+            }
         }
     }
 
+    pub fn begin_session(&mut self) {
+        self.autosave.begin_session();
+    }
+
+    /// Must be called when the window closes, otherwise the next launch offers to restore
+    pub fn end_session(&mut self) {
+        // Unanswered, the question is asked again at the next launch
+        if !self.restore_pending() {
+            self.autosave.end_session(&self.board);
+        }
+    }
+
+    /// The autosaves must not replace the session that may be restored
+    fn restore_pending(&self) -> bool {
+        self.prompt.as_ref().is_some_and(|prompt| prompt.kind == PromptKind::Restore)
+    }
+
+    pub fn set_import_options(&mut self, options: ImportOptions) {
+        self.image_options = options;
+    }
+
+    pub fn set_fill_density(&mut self, density: f64) {
+        self.fill_density = density;
+    }
+
+    pub fn set_inertia(&mut self, inertia: bool) {
+        self.inertia = inertia;
+    }
+
+    pub fn set_step_count(&mut self, count: u64) {
+        self.step_count = count;
+    }
+
+    /// In megabytes
+    pub fn set_history_budget(&mut self, megabytes: usize) {
+        self.history.set_budget(megabytes << 20);
+    }
+
+    /// Index the directory in the background so that it can be browsed
+    pub fn watch_patterns(&mut self, dir: PathBuf) {
+        self.pattern_index = Some(PatternIndex::watch(dir));
+    }
+
+    /// Put a pattern or an image centered on the cursor
+    pub fn load_file(&mut self, path: &Path) {
+        if self.state != GameState::Pause {
+            self.show_message(format!("Pause the game before loading {}", path.display()));
+            return;
+        }
+        let pattern = match files::read_pattern(path, &self.image_options) {
+            Ok(pattern) => pattern,
+            Err(error) => {
+                self.show_message(error);
+                return;
+            }
+        };
+        let (rows, cols) = pattern.get_shape();
+        let GridPosition(col, row) = self.origin_under_cursor([cols as f64 / 2.0, rows as f64 / 2.0]);
+        self.begin_edit();
+        self.board.paste(&pattern, row, col);
+        self.end_edit();
+    }
+
+    /// Top left cell of a pattern held at `anchor` by the cursor, or by the camera
+    /// center if the cursor is not known
+    fn origin_under_cursor(&self, anchor: [f64; 2]) -> GridPosition {
+        let cursor = match self.mouse_coords {
+            Some(position) => screen_to_world(position, &self.camera),
+            None => self.camera.center()
+        };
+        world_to_grid([
+            (cursor[0] - anchor[0]).round().max(0.0),
+            (cursor[1] - anchor[1]).round().max(0.0)
+        ])
+    }
+
+    /// Text for the title bar of the window
+    pub fn title(&self) -> String {
+        let mut title = format!("{} - Generation {} - {}", WINDOW_TITLE, self.timeline.generation(), self.rate_text());
+        if let (Some(browser), Some(index)) = (&self.browser, &self.pattern_index) {
+            title.push_str(" - ");
+            title.push_str(&browser.title(index.entries()));
+        }
+        else if let Some(ghost) = &self.ghost {
+            match ghost.library_index {
+                Some(index) => title.push_str(&format!(" - Library {}/{}: {} (Tab for next, click to place, Esc to cancel)",
+                    index + 1, LIBRARY.len(), ghost.name)),
+                None => title.push_str(&format!(" - Placing {} (click to place, Esc to cancel)", ghost.name))
+            }
+        }
+        else if let Some(prompt) = &self.prompt {
+            match prompt.kind {
+                PromptKind::Generation => title.push_str(&format!(" - Run until generation: {}_ (Enter to run, Esc to cancel)", prompt.text)),
+                PromptKind::Coordinate => title.push_str(&format!(" - Go to x,y: {}_ (Enter to go, Esc to cancel)", prompt.text)),
+                PromptKind::Restore => title.push_str(" - The last session did not exit properly, restore it ? (Y or Enter to restore, N to start empty)")
+            }
+        }
+        else if let Some((message, _)) = &self.message {
+            title.push_str(&format!(" - {}", message));
+        }
+        else if let Some(target) = self.target_generation {
+            title.push_str(&format!(" - Running until generation {}", target));
+        }
+        else if let Some(status) = self.timeline.status() {
+            title.push_str(&format!(" - {}", status));
+        }
+        else if self.follow.is_some() {
+            title.push_str(" - Following (Y to stop)");
+        }
+        else if self.tool == Tool::Draw {
+            title.push_str(&format!(" - Brush {}", BRUSHES[self.brush].name()));
+        }
+        else if self.tool != Tool::Select {
+            title.push_str(&format!(" - {}", self.tool.name()));
+        }
+        title
+    }
+
     pub fn resize(&mut self, args: &ResizeArgs) {
         self.camera.resize(args.window_size);
     }
 
     pub fn render(&mut self, args: &RenderArgs) {
         use graphics::*;
+        let ghost_origin = self.ghost.as_ref()
+            .filter(|_| self.mouse_coords.is_some())
+            .map(|ghost| self.origin_under_cursor(ghost.anchor));
+        self.update_preview();
         let gl = &mut self.gl;
 
+        let theme = THEMES[self.theme].1;
         let context = gl.draw_begin(args.viewport());
-        clear(BG_COLOR, gl);
-        let transform = context.transform.trans(0.0, 0.0).rot_deg(0.0);
-        let (rows, cols) = self.board.get_shape();
-        for y in 0..rows {
-            for x in 0..cols{
-                if self.board.get(y, x).unwrap() { // Cell is alive ?
-                    if let Some(screen_pos) = GridPosition(x,y).to_screen_position(&self.camera) {
-                        rectangle(
-                            CELL_COLOR,
-                            rectangle::square(screen_pos[0], screen_pos[1], self.camera.cell_lenght()),
-                            transform.clone(),
-                            gl
-                        );
-                    }
-                }
-            }
+        clear(theme.background, gl);
+        let block = lod::block_size(self.camera.cell_lenght());
+        if block > 1 {
+            lod::render(&self.board, &self.camera, block, &theme, &context, gl);
         }
-        
+        else {
+            runs::render(&self.board, [0, 0], &self.camera, theme.cell, &context, gl);
+        }
+
+        if let (Some(ghost), Some(GridPosition(origin_x, origin_y))) = (&self.ghost, ghost_origin) {
+            let ghost_color = [theme.cell[0], theme.cell[1], theme.cell[2], GHOST_ALPHA];
+            runs::render(&ghost.pattern, [origin_x, origin_y], &self.camera, ghost_color, &context, gl);
+        }
+
+        if let Some(preview) = &self.preview {
+            // Erased cells are covered by the background
+            let [r, g, b, _] = if preview.alive {theme.cell} else {theme.background};
+            let alpha = if preview.alive {GHOST_ALPHA} else {1.0 - GHOST_ALPHA};
+            runs::render(&preview.cells, preview.origin, &self.camera, [r, g, b, alpha], &context, gl);
+        }
+
+        if let Some(rect) = &self.selection {
+            selection::render_outline(rect, &self.camera, theme.cell, &context, gl);
+        }
+
+        self.timeline.render_scrubber(&theme, self.camera.draw_size(), &context, gl);
+        if self.show_minimap {
+            minimap::render(&self.board, &self.camera, &theme, &context, gl);
+        }
+
+        if let (Some(browser), Some(index)) = (&self.browser, &self.pattern_index) {
+            browser.render(index.entries(), &theme, self.camera.draw_size(), &context, gl);
+        }
+
         gl.draw_end();
     }
 
     pub fn update(&mut self, args: &UpdateArgs) {
-        
+        if let Some((_, left)) = &mut self.message {
+            *left -= args.dt;
+            if *left <= 0.0 {
+                self.message = None;
+            }
+        }
+
         let lshift = self.pressed_buttons.contains(&Button::Keyboard(Key::LShift));
 
         // Zooming and dezooming
-        let zoom = self.pressed_buttons.contains(&Button::Keyboard(Key::S));
-        let dezoom = self.pressed_buttons.contains(&Button::Keyboard(Key::X));
+        let zoom = self.pressed_buttons.contains(&Button::Keyboard(Key::S)) && !self.chords.contains(&Key::S);
+        let dezoom = self.pressed_buttons.contains(&Button::Keyboard(Key::X)) && !self.chords.contains(&Key::X);
         if zoom && !dezoom {
             self.camera.zoom(args.dt, lshift);
         }
         else if !zoom && dezoom {
             self.camera.dezoom(args.dt, lshift);
         }
+        self.camera.update_zoom(args.dt);
 
         // Moving the camera up, down, right or left
         let speed = if lshift {SUPER_SPEED} else {SPEED};
@@ -134,133 +505,952 @@ impl Game {
         else if !right && left {
             self.camera.move_max([args.dt * -speed, 0.0]);
         }
+        if up || down || right || left {
+            self.camera_velocity = [0.0, 0.0];
+            self.follow = None;
+        }
+
+        // Inertia of a released pan
+        if self.camera_velocity != [0.0, 0.0] {
+            self.camera.move_max([self.camera_velocity[0] * args.dt, self.camera_velocity[1] * args.dt]);
+            let friction = (-PAN_FRICTION * args.dt).exp();
+            self.camera_velocity = [self.camera_velocity[0] * friction, self.camera_velocity[1] * friction];
+            if self.camera_velocity[0].hypot(self.camera_velocity[1]) < MIN_PAN_VELOCITY {
+                self.camera_velocity = [0.0, 0.0];
+            }
+        }
+
+        // Scrolling while drawing or selecting close to the edges of the window
+        let dragging = self.stroke.is_some() || self.shape.is_some() || self.selection_start.is_some()
+            || self.ghost.as_ref().is_some_and(|ghost| ghost.moving);
+        if let (true, Some(position)) = (dragging, self.mouse_coords) {
+            let [width, height] = self.camera.draw_size();
+            let edge = |coordinate: f64, size: f64| {
+                if coordinate < EDGE_SCROLL_MARGIN {-1.0} else if coordinate > size - EDGE_SCROLL_MARGIN {1.0} else {0.0}
+            };
+            let direction = [edge(position[0], width), edge(position[1], height)];
+            if direction != [0.0, 0.0] {
+                self.camera.move_max([direction[0] * args.dt * speed, direction[1] * args.dt * speed]);
+                self.update_mouse_position(position);
+            }
+        }
         
 
         // Updating the cell grid with a new generation
         if self.state == GameState::Play {
-            self.total_dt += args.dt * f64::from(self.speed);
-            if self.total_dt > DT_BEFORE_UPDATE {
-                self.total_dt -= DT_BEFORE_UPDATE;
+            let updates = self.simulation.poll();
+            self.apply_updates(updates);
+            if self.hyperspeed {
+                self.hyperspeed_time += args.dt;
+                if self.hyperspeed_time > HYPERSPEED_INTERVAL {
+                    self.hyperspeed_time = 0.0;
+                    self.change_step_exponent(true);
+                }
+            }
+        }
+
+        self.update_follow();
+        self.camera.update_flight(args.dt);
+
+        // Running until a generation, unless the board is being edited
+        if let (Some(target), GameState::Pause, None) = (self.target_generation, self.state, &self.edit_start) {
+            let start = Instant::now();
+            while self.timeline.generation() < target && start.elapsed().as_secs_f64() < FRAME_WORK_BUDGET {
                 self.board.next_generation();
+                self.timeline.advance(&self.board);
+            }
+            if self.timeline.generation() >= target {
+                self.stop_running_until();
             }
         }
+
+        if !self.restore_pending() {
+            self.autosave.update(args.dt, &self.board);
+        }
+        if let Some(index) = &mut self.pattern_index {
+            index.poll();
+        }
     }
 
     pub fn clear(&mut self) {
+        self.begin_edit();
         self.board.clear();
     }
 
+    fn next_theme(&mut self) {
+        self.theme = (self.theme + 1) % THEMES.len();
+    }
+
     fn switch_pause(&mut self) {
+        self.stop_running_until();
+        self.cancel_ghost();
+        // A stroke or a shape does not go on across the switch, the cells drawn so far are kept
+        self.stroke = None;
+        self.shape = None;
+        self.end_edit();
         self.state = !self.state;
+        match self.state {
+            GameState::Play => {
+                self.history.begin_run(&self.board, self.timeline.generation());
+                self.simulation.play(&self.board, self.timeline.generation(), self.rate, self.step_exponent);
+            },
+            GameState::Pause => {
+                // The board stays as last shown, with the cells drawn since
+                let pending = self.simulation.pause();
+                if !pending.is_empty() {
+                    for edit in &pending {
+                        edit.apply(&mut self.board);
+                    }
+                    self.timeline.rewrite(&self.board);
+                }
+                self.history.end_run(&self.board, self.timeline.generation());
+            }
+        }
+    }
+
+    /// Show the latest board computed by the worker, with the cells drawn since
+    fn apply_updates(&mut self, updates: Vec<Update>) {
+        if updates.is_empty() {
+            return;
+        }
+        for update in updates {
+            match update {
+                Update::Show(snapshot) => {
+                    self.timeline.advance_to(snapshot.generation);
+                    self.board = snapshot.board;
+                },
+                Update::Keyframe { generation, rle } => self.timeline.add_keyframe(generation, rle),
+                Update::Edited { snapshot, rle } => {
+                    self.timeline.rewrite_at(snapshot.generation, rle);
+                    self.board = snapshot.board;
+                }
+            }
+        }
+        for edit in self.simulation.pending() {
+            edit.apply(&mut self.board);
+        }
+    }
+
+    /// Set the cells on the board, and on the board of the worker while the game plays
+    fn paint(&mut self, edit: Edit) {
+        self.begin_edit();
+        edit.apply(&mut self.board);
+        if self.state == GameState::Play {
+            self.simulation.edit(edit);
+        }
+    }
+
+    fn show_message(&mut self, message: String) {
+        self.message = Some((message, MESSAGE_DURATION));
+    }
+
+    /// Remember the board before the first change of an edit, edits can only
+    /// happen in pause
+    fn begin_edit(&mut self) {
+        if self.edit_start.is_none() && self.state == GameState::Pause {
+            self.edit_start = Some(self.board.clone());
+        }
+    }
+
+    /// Record the changes unless a stroke, a shape or a move is still in progress
+    fn end_edit(&mut self) {
+        let in_progress = self.stroke.is_some() || self.shape.is_some()
+            || self.ghost.as_ref().is_some_and(|ghost| ghost.moving);
+        if in_progress {
+            return;
+        }
+        if let Some(before) = self.edit_start.take() {
+            // The generations run before an edit are undone after it
+            let split_run = self.target_generation.is_some() && before != self.board;
+            if split_run {
+                self.history.end_run(&before, self.timeline.generation());
+                self.history.begin_run(&self.board, self.timeline.generation());
+            }
+            if self.history.record_edit(&before, &self.board) {
+                self.timeline.rewrite(&self.board);
+            }
+        }
+    }
+
+    /// Runs are undone as a whole, the game is paused first
+    fn undo(&mut self) {
+        self.stop_running_until();
+        if self.stroke.is_some() || self.shape.is_some() || self.ghost.as_ref().is_some_and(|ghost| ghost.moving) {
+            return;
+        }
+        if self.state == GameState::Play {
+            self.switch_pause();
+        }
+        match self.history.undo(&mut self.board) {
+            Ok(Some(generation)) => self.timeline.restore(generation, &self.board),
+            Ok(None) => self.timeline.rewrite(&self.board),
+            Err(()) => self.show_message(String::from("Nothing to undo"))
+        }
+        self.edit_start = None; // Undoing is not an edit
+    }
+
+    fn redo(&mut self) {
+        self.stop_running_until();
+        if self.state == GameState::Play || self.stroke.is_some() || self.shape.is_some() {
+            return;
+        }
+        match self.history.redo(&mut self.board) {
+            Ok(Some(generation)) => self.timeline.restore(generation, &self.board),
+            Ok(None) => self.timeline.rewrite(&self.board),
+            Err(()) => self.show_message(String::from("Nothing to redo"))
+        }
+        self.edit_start = None;
+    }
+
+    /// Beyond `MAX_RATE` the game runs as fast as possible
+    fn increase_rate(&mut self) {
+        self.rate = if self.rate * RATE_FACTOR > MAX_RATE {f64::INFINITY} else {self.rate * RATE_FACTOR};
+        self.simulation.set_rate(self.rate, self.step_exponent);
     }
 
-    fn increase_updates(&mut self) {
-        match self.speed {
-            Speed::SuperSlow => {self.speed = Speed::VerySlow},
-            Speed::VerySlow => {self.speed = Speed::Slow},
-            Speed::Slow => {self.speed = Speed::Normal},
-            Speed::Normal => {self.speed = Speed::Fast},
-            Speed::Fast => {self.speed = Speed::VeryFast},
-            Speed::VeryFast => {self.speed = Speed::SuperFast},
-            Speed::SuperFast => {self.speed = Speed::InsanelyFast},
-            Speed::InsanelyFast => {}
+    fn decrease_rate(&mut self) {
+        self.rate = (self.rate / RATE_FACTOR).clamp(MIN_RATE, MAX_RATE);
+        self.simulation.set_rate(self.rate, self.step_exponent);
+    }
+
+    fn change_step_exponent(&mut self, increase: bool) {
+        self.step_exponent = if increase {(self.step_exponent + 1).min(MAX_STEP_EXPONENT)} else {self.step_exponent.saturating_sub(1)};
+        self.simulation.set_rate(self.rate, self.step_exponent);
+    }
+
+    fn switch_hyperspeed(&mut self) {
+        self.hyperspeed = !self.hyperspeed;
+        self.hyperspeed_time = 0.0;
+    }
+
+    /// Like `12.3 gen/s` or `4.0 steps/s of 2^5 gen`
+    fn rate_text(&self) -> String {
+        let rate = if self.rate.is_infinite() {String::from("unlimited")} else {format!("{:.1}", self.rate)};
+        let mut text = if self.step_exponent == 0 {format!("{} gen/s", rate)}
+            else {format!("{} steps/s of 2^{} gen", rate, self.step_exponent)};
+        if self.hyperspeed {
+            text.push_str(" (hyperspeed)");
+        }
+        text
+    }
+
+    /// Open the library, or go to its next pattern
+    fn next_library_pattern(&mut self, backward: bool) {
+        if self.state != GameState::Pause {
+            return;
+        }
+        let index = match &self.ghost {
+            Some(Ghost { library_index: Some(index), .. }) if backward => (index + LIBRARY.len() - 1) % LIBRARY.len(),
+            Some(Ghost { library_index: Some(index), .. }) => (index + 1) % LIBRARY.len(),
+            _ => 0
         };
+        self.ghost = Some(Ghost::new(LIBRARY[index].pattern(), LIBRARY[index].name.to_string(), Some(index)));
     }
 
-    fn decrease_updates(&mut self) {
-        match self.speed {
-            Speed::InsanelyFast => {self.speed = Speed::SuperFast},
-            Speed::SuperFast => {self.speed = Speed::VeryFast},
-            Speed::VeryFast => {self.speed = Speed::Fast},
-            Speed::Fast => {self.speed = Speed::Normal},
-            Speed::Normal => {self.speed = Speed::Slow},
-            Speed::Slow => {self.speed = Speed::VerySlow},
-            Speed::VerySlow => {self.speed = Speed::SuperSlow},
-            Speed::SuperSlow => {}
+    fn open_browser(&mut self) {
+        if self.state != GameState::Pause {
+            return;
+        }
+        if self.pattern_index.is_some() {
+            self.cancel_ghost();
+            self.browser = Some(Browser::default());
+        }
+        else {
+            self.show_message(String::from("No pattern directory, start with --patterns <DIR>"));
+        }
+    }
+
+    /// Keys go to the browser while it is open
+    fn handle_browser_key(&mut self, key: Key) {
+        let (Some(browser), Some(index)) = (&mut self.browser, &self.pattern_index) else {
+            return;
         };
+        let draw_size = self.camera.draw_size();
+        match key {
+            Key::Escape => self.browser = None,
+            Key::Backspace => browser.erase_char(),
+            Key::Left => browser.move_selection(-1, 0, index.entries(), draw_size),
+            Key::Right => browser.move_selection(1, 0, index.entries(), draw_size),
+            Key::Up => browser.move_selection(0, -1, index.entries(), draw_size),
+            Key::Down => browser.move_selection(0, 1, index.entries(), draw_size),
+            Key::Return => {
+                let selected = browser.selected(index.entries()).map(|entry| entry.path.clone());
+                self.browser = None;
+                if let Some(path) = selected {
+                    match files::read_pattern_file(&path) {
+                        Ok(file) => self.ghost = Some(Ghost::new(file.pattern, file.name, None)),
+                        Err(error) => self.show_message(error)
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Zoom around the cursor, one level per notch of the wheel
+    pub fn handle_scroll(&mut self, scroll: f64) {
+        if self.browser.is_some() || scroll == 0.0 {
+            return;
+        }
+        let cursor = self.mouse_coords.unwrap_or_else(|| {
+            let [width, height] = self.camera.draw_size();
+            [width / 2.0, height / 2.0]
+        });
+        self.camera.wheel_zoom(scroll > 0.0, cursor);
     }
 
-    /*
-    fn load_glider_canon(&mut self) {
+    /// Typed characters, only used by the browser filter and the prompt
+    pub fn handle_text(&mut self, text: &str) {
+        if let Some(browser) = &mut self.browser {
+            browser.type_text(text);
+        }
+        else if let Some(prompt) = &mut self.prompt {
+            let accepted = |c: &char| prompt.kind != PromptKind::Restore
+                && (c.is_ascii_digit() || (prompt.kind == PromptKind::Coordinate && *c == ','));
+            prompt.text.extend(text.chars().filter(accepted));
+        }
+    }
+
+    /// The placed cells are added to those of the board, and become the selection
+    fn place_ghost(&mut self) {
+        if let Some(ghost) = self.ghost.take() {
+            let GridPosition(col, row) = self.origin_under_cursor(ghost.anchor);
+            self.begin_edit();
+            self.board.paste(&ghost.pattern, row, col);
+            let (height, width) = ghost.pattern.get_shape();
+            self.selection = Some(GridRect { x: col, y: row, width, height });
+        }
+    }
+
+    /// Cells being moved can't be lost, they are dropped where they are
+    fn cancel_ghost(&mut self) {
+        if self.ghost.as_ref().is_some_and(|ghost| ghost.moving) {
+            self.place_ghost();
+        }
+        self.ghost = None;
+    }
+
+    fn ctrl(&self) -> bool {
+        self.pressed_buttons.contains(&Button::Keyboard(Key::LCtrl))
+            || self.pressed_buttons.contains(&Button::Keyboard(Key::RCtrl))
+    }
+
+    fn copy_selection(&mut self) {
+        if let Some(rect) = self.selection {
+            self.clipboard = Some(self.board.extract(rect));
+        }
+    }
+
+    fn delete_selection(&mut self) {
+        if let (Some(rect), GameState::Pause) = (self.selection, self.state) {
+            self.begin_edit();
+            self.board.clear_rect(rect);
+        }
+    }
+
+    fn cut_selection(&mut self) {
         if self.state == GameState::Pause {
-            let board_size = self.board.get_shape();
-            if board_size.0 > 35 && board_size.1 > 8 {
-                self.clear();
-                
-                // Left square
-                self.board.switch_state_at(4, 0).unwrap();
-                self.board.switch_state_at(4, 1).unwrap();
-                self.board.switch_state_at(5, 0).unwrap();
-                self.board.switch_state_at(5, 1).unwrap();
-
-                // Half-circle
-                self.board.switch_state_at(2, 13).unwrap();
-                self.board.switch_state_at(2, 12).unwrap();
-                self.board.switch_state_at(3, 11).unwrap();
-                self.board.switch_state_at(4, 10).unwrap();
-                self.board.switch_state_at(5, 10).unwrap();
-                self.board.switch_state_at(6, 10).unwrap();
-                self.board.switch_state_at(7, 11).unwrap();
-                self.board.switch_state_at(8, 12).unwrap();
-                self.board.switch_state_at(8, 13).unwrap();
-
-                // Small point
-                self.board.switch_state_at(5, 14).unwrap();
-
-                // Left ship
-                self.board.switch_state_at(3, 15).unwrap();
-                self.board.switch_state_at(4, 16).unwrap();
-                self.board.switch_state_at(5, 16).unwrap();
-                self.board.switch_state_at(5, 17).unwrap();
-                self.board.switch_state_at(6, 16).unwrap();
-                self.board.switch_state_at(7, 15).unwrap();
-
-                // Right ship
-                self.board.switch_state_at(4,20).unwrap();
-                self.board.switch_state_at(3,20).unwrap();
-                self.board.switch_state_at(2,20).unwrap();
-                self.board.switch_state_at(4,21).unwrap();
-                self.board.switch_state_at(3,21).unwrap();
-                self.board.switch_state_at(2,21).unwrap();
-                self.board.switch_state_at(1,22).unwrap();
-                self.board.switch_state_at(5,22).unwrap();
-
-                // Right bars
-                self.board.switch_state_at(0, 24).unwrap();
-                self.board.switch_state_at(1, 24).unwrap();
-                self.board.switch_state_at(5, 24).unwrap();
-                self.board.switch_state_at(6, 24).unwrap();
-
-                // Right square
-                self.board.switch_state_at(2, 34).unwrap();
-                self.board.switch_state_at(2, 35).unwrap();
-                self.board.switch_state_at(3, 34).unwrap();
-                self.board.switch_state_at(3, 35).unwrap();
+            self.copy_selection();
+            self.delete_selection();
+        }
+    }
+
+    /// The clipboard follows the cursor until it is clicked into place
+    fn paste_clipboard(&mut self) {
+        if let (Some(pattern), GameState::Pause) = (&self.clipboard, self.state) {
+            self.ghost = Some(Ghost::new(pattern.clone(), String::from("clipboard"), None));
+        }
+    }
+
+    /// Lift the selected cells so that they follow the cursor while the button is held
+    fn start_moving_selection(&mut self, grab: GridPosition) {
+        let Some(rect) = self.selection else {
+            return;
+        };
+        let pattern = self.board.extract(rect);
+        self.begin_edit();
+        self.board.clear_rect(rect);
+        let cursor = self.mouse_coords.map_or([grab.0 as f64, grab.1 as f64], |position| screen_to_world(position, &self.camera));
+        let anchor = [cursor[0] - rect.x as f64, cursor[1] - rect.y as f64];
+        self.ghost = Some(Ghost { pattern, name: String::from("selection"), library_index: None, anchor, moving: true });
+        self.selection = None;
+    }
+
+    /// Lift the selected cells and put `pattern` at `[x, y]` as the selection, over cleared cells
+    ///
+    /// The cells it covers come back when it is nudged or transformed again, so
+    /// that moving it around leaves the rest of the board as it was.
+    fn replace_selection(&mut self, pattern: &GameGrid, x: usize, y: usize) {
+        let Some(rect) = self.selection else {
+            return;
+        };
+        let revision = self.board.revision();
+        self.begin_edit();
+        self.board.clear_rect(rect);
+        if let Some(covered) = self.covered.take().filter(|covered| covered.rect == rect && covered.revision == revision) {
+            self.board.paste(&covered.cells, rect.y, rect.x);
+        }
+        let (height, width) = pattern.get_shape();
+        let destination = GridRect { x, y, width, height };
+        let cells = self.board.extract(destination);
+        self.board.clear_rect(destination);
+        self.board.paste(pattern, y, x);
+        self.covered = Some(Covered { rect: destination, cells, revision: self.board.revision() });
+        self.selection = Some(destination);
+    }
+
+    /// Replace the selected cells by a transformed copy, centered on the same cell
+    fn transform_selection(&mut self, transform: impl Fn(&GameGrid) -> GameGrid) {
+        let (Some(rect), GameState::Pause) = (self.selection, self.state) else {
+            return;
+        };
+        let transformed = transform(&self.board.extract(rect));
+        let (height, width) = transformed.get_shape();
+        let (rows, cols) = self.board.get_shape();
+        let x = (rect.x as isize + (rect.width as isize - width as isize) / 2).clamp(0, (cols - width) as isize) as usize;
+        let y = (rect.y as isize + (rect.height as isize - height as isize) / 2).clamp(0, (rows - height) as isize) as usize;
+        self.replace_selection(&transformed, x, y);
+    }
+
+    /// Move the selected cells by one cell, staying inside the grid
+    fn nudge_selection(&mut self, dx: isize, dy: isize) {
+        let Some(rect) = self.selection else {
+            return;
+        };
+        let (rows, cols) = self.board.get_shape();
+        let x = (rect.x as isize + dx).clamp(0, (cols - rect.width) as isize) as usize;
+        let y = (rect.y as isize + dy).clamp(0, (rows - rect.height) as isize) as usize;
+        self.replace_selection(&self.board.extract(rect), x, y);
+    }
+
+    fn invert_selection(&mut self) {
+        if let (Some(rect), GameState::Pause) = (self.selection, self.state) {
+            self.begin_edit();
+            self.board.invert_rect(rect);
+        }
+    }
+
+    fn fill_selection_randomly(&mut self) {
+        if let (Some(rect), GameState::Pause) = (self.selection, self.state) {
+            self.begin_edit();
+            for row in rect.y..rect.y + rect.height {
+                for col in rect.x..rect.x + rect.width {
+                    let alive = self.random.next_f64() < self.fill_density;
+                    let _ = self.board.set_state_at(row, col, alive);
+                }
             }
         }
-    } */
+    }
+
+    fn clear_outside_selection(&mut self) {
+        if let (Some(rect), GameState::Pause) = (self.selection, self.state) {
+            self.begin_edit();
+            self.board.clear_outside(rect);
+        }
+    }
+
+    /// Save the selected cells as RLE in the pattern directory, or else in the working directory
+    fn save_selection(&mut self) {
+        let Some(rect) = self.selection else {
+            return;
+        };
+        let dir = self.pattern_index.as_ref().map_or(Path::new("."), PatternIndex::dir);
+        let path = files::timestamped_path(dir, "selection", "rle");
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let text = format!("#N {}\n{}", name, rle::encode(&self.board.extract(rect)));
+        match std::fs::write(&path, text) {
+            Ok(()) => self.show_message(format!("Selection saved to {}", path.display())),
+            Err(error) => self.show_message(format!("Could not save the selection: {}", error))
+        }
+    }
+
+    /// Save what the window shows as a PNG in the working directory
+    fn take_screenshot(&mut self) {
+        let path = files::timestamped_path(Path::new("."), "screenshot", "png");
+        match raster::render_view(&self.board, &self.camera, &THEMES[self.theme].1).write_png(&path) {
+            Ok(()) => self.show_message(format!("Screenshot saved to {}", path.display())),
+            Err(error) => self.show_message(format!("Screenshot failed: {}", error))
+        }
+    }
+
+    /// Save the selection, or else what the window shows, as an SVG in the working directory
+    ///
+    /// The selected cells keep the size they have on screen, at least a pixel.
+    fn export_svg(&mut self) {
+        let framing = self.selection.map(|rect| Camera::framing(rect, self.camera.cell_lenght().max(1.0)));
+        let name = if framing.is_some() {"selection"} else {"view"};
+        let path = files::timestamped_path(Path::new("."), name, "svg");
+        let text = svg::render_svg(&self.board, framing.as_ref().unwrap_or(&self.camera), &THEMES[self.theme].1, SvgOptions::default());
+        match std::fs::write(&path, text) {
+            Ok(()) => self.show_message(format!("SVG saved to {}", path.display())),
+            Err(error) => self.show_message(format!("SVG export failed: {}", error))
+        }
+    }
 
     pub fn update_mouse_position(&mut self, position: [f64;2]) {
         self.mouse_coords = Some(position);
+        if self.pan.is_some() {
+            return self.pan_to(position);
+        }
+        if self.minimap_dragging {
+            return self.center_from_minimap(position);
+        }
+        if self.scrubbing {
+            if let Some(generation) = self.timeline.generation_at(position, self.camera.draw_size()) {
+                self.seek(generation);
+            }
+            return;
+        }
+        match self.tool {
+            Tool::Draw => self.edit_at_position(position),
+            Tool::Select => self.drag_selection(position),
+            _ => {} // Shapes are previewed from the cursor position when rendering
+        }
+    }
+
+    fn start_pan(&mut self) {
+        if let Some(position) = self.mouse_coords {
+            self.follow = None;
+            self.pan = Some(Pan { last: position, last_time: Instant::now(), velocity: [0.0, 0.0] });
+            self.camera_velocity = [0.0, 0.0];
+        }
+    }
+
+    /// Keep the world position that was under the cursor under it
+    fn pan_to(&mut self, position: ScreenPosition) {
+        let Some(pan) = &mut self.pan else {
+            return;
+        };
+        let from = screen_to_world(pan.last, &self.camera);
+        let to = screen_to_world(position, &self.camera);
+        let delta = [from[0] - to[0], from[1] - to[1]];
+        self.camera.move_max(delta);
+        // Several moves can come in the same frame, a moving average does not
+        // blow up for them the way the last move alone would
+        let dt = pan.last_time.elapsed().as_secs_f64();
+        if dt > 0.0 {
+            let weight = 1.0 - (-dt / PAN_VELOCITY_WINDOW).exp();
+            let velocity = pan.velocity;
+            pan.velocity = [0, 1].map(|axis| velocity[axis] + (delta[axis] / dt - velocity[axis]) * weight);
+            let [_, _, width, _] = self.camera.view();
+            let speed = pan.velocity[0].hypot(pan.velocity[1]);
+            if speed > MAX_PAN_SPEED * width {
+                pan.velocity = pan.velocity.map(|v| v * MAX_PAN_SPEED * width / speed);
+            }
+        }
+        pan.last = position;
+        pan.last_time = Instant::now();
+    }
+
+    /// The view keeps the velocity of the pan, unless the cursor stopped before the release
+    fn end_pan(&mut self) {
+        if let Some(pan) = self.pan.take() {
+            if self.inertia && pan.last_time.elapsed().as_secs_f64() < 0.1 {
+                self.camera_velocity = pan.velocity;
+            }
+        }
+    }
+
+    fn fit_pattern(&mut self) {
+        match self.board.bounding_box() {
+            Some(bounds) => {
+                self.follow = None;
+                self.camera.fit(bounds);
+            },
+            None => self.show_message(String::from("Nothing to fit, the board is empty"))
+        }
+    }
+
+    fn fit_selection(&mut self) {
+        if let Some(rect) = self.selection {
+            self.follow = None;
+            self.camera.fit(rect);
+        }
+    }
+
+    /// Follow the cells of the selection if there is one, the whole pattern otherwise
+    fn switch_follow(&mut self, frame: bool) {
+        self.follow = match self.follow {
+            Some(_) => None,
+            None => Some(Follow {
+                object: self.selection.and_then(|rect| self.board.bounding_box_within(rect)),
+                frame,
+                generation: None,
+            })
+        };
+    }
+
+    /// Find the followed object again at each new generation, looking around where it was
+    fn update_follow(&mut self) {
+        let Some(follow) = &mut self.follow else {
+            return;
+        };
+        let generation = self.timeline.generation();
+        if follow.generation == Some(generation) {
+            return;
+        }
+        // Spaceships move at most one cell per generation
+        let margin = FOLLOW_MARGIN + follow.generation.map_or(0, |previous| generation.abs_diff(previous).min(64) as usize);
+        let (rows, cols) = self.board.get_shape();
+        let region = match follow.object {
+            Some(object) => {
+                let x = object.x.saturating_sub(margin);
+                let y = object.y.saturating_sub(margin);
+                let around = GridRect {
+                    x,
+                    y,
+                    width: (object.x + object.width + margin).min(cols) - x,
+                    height: (object.y + object.height + margin).min(rows) - y,
+                };
+                self.board.bounding_box_within(around)
+            },
+            None => self.board.bounding_box()
+        };
+        let Some(region) = region else {
+            self.show_message(String::from("Nothing left to follow"));
+            self.follow = None;
+            return;
+        };
+
+        if follow.frame {
+            self.camera.fit(region);
+        }
+        else if let Some(centroid) = self.board.centroid(region) {
+            self.camera.fly_to(centroid, None);
+        }
+        if follow.object.is_some() {
+            follow.object = Some(region);
+        }
+        follow.generation = Some(generation);
+    }
+
+    fn on_minimap(&self) -> bool {
+        self.show_minimap && self.mouse_coords.is_some_and(|position| minimap::world_at(position, self.camera.draw_size()).is_some())
+    }
+
+    /// Center the view on the world position under the cursor
+    fn center_from_minimap(&mut self, position: ScreenPosition) {
+        if let Some(world) = minimap::world_at(position, self.camera.draw_size()) {
+            self.follow = None;
+            let center = self.camera.center();
+            self.camera.move_max([world[0] - center[0], world[1] - center[1]]);
+        }
+    }
+
+    fn on_scrubber(&self) -> bool {
+        self.mouse_coords.is_some_and(|position| self.timeline.generation_at(position, self.camera.draw_size()).is_some())
+    }
+
+    fn drag_selection(&mut self, position: [f64;2]) {
+        if let Some(start) = &self.selection_start {
+            let end = screen_to_grid(position, &self.camera);
+            self.selection = Some(selection::rect_from_corners(start, &end, self.board.get_shape()));
+        }
+    }
+
+    /// Left click with the selection tool
+    fn press_selection(&mut self, position: [f64;2]) {
+        if self.state != GameState::Pause {
+            return;
+        }
+        let grid_position = screen_to_grid(position, &self.camera);
+        match self.selection {
+            Some(rect) if selection::contains(&rect, &grid_position) => self.start_moving_selection(grid_position),
+            _ => {
+                self.selection = Some(selection::rect_from_corners(&grid_position, &grid_position, self.board.get_shape()));
+                self.selection_start = Some(grid_position);
+            }
+        }
+    }
+
+    /// Starting on a dead cell draws and on a live cell erases,
+    /// unless erasing is asked or LShift forces drawing and LAlt erasing
+    fn start_stroke(&mut self, position: [f64;2], erase: bool) {
+        let cell = self.cell_at(position);
+        // Clicks outside the grid are ignored
+        let Some(alive) = self.cell_state(cell) else {
+            return;
+        };
+        let alive = if erase || self.pressed_buttons.contains(&Button::Keyboard(Key::LAlt)) {false}
+            else if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) {true}
+            else {!alive};
+        self.stroke = Some(Stroke { alive, last: cell });
         self.edit_at_position(position);
     }
 
+    /// Paint every cell crossed since the previous mouse move, the cursor samples being sparse
     fn edit_at_position(&mut self, position: [f64;2]) {
-        if !self.pressed_buttons.contains(&Button::Mouse(MouseButton::Left)) || self.state != GameState::Pause {
+        let end = self.cell_at(position);
+        let Some(stroke) = &mut self.stroke else {
             return;
+        };
+        let offsets = BRUSHES[self.brush].offsets();
+        let mut cells = Vec::new();
+        for [x, y] in shapes::line(stroke.last, end) {
+            for [dx, dy] in &offsets {
+                let (col, row) = (x + dx, y + dy);
+                if col >= 0 && row >= 0 {
+                    cells.push((row as usize, col as usize));
+                }
+            }
         }
-        let grid_position = screen_to_grid(position, &self.camera);
-        if !self.changed_tiles.contains(&grid_position) {
-            self.board.switch_state_at(grid_position.1, grid_position.0).unwrap_or_else(
-                |_| panic!("Try click ({:?},{:?})", grid_position.0, grid_position.1)
-            );
-            self.changed_tiles.insert(grid_position);
+        stroke.last = end;
+        let alive = stroke.alive;
+        self.paint(Edit { cells, alive });
+    }
+
+    fn start_shape(&mut self, position: [f64;2], erase: bool) {
+        let start = self.cell_at(position);
+        if self.cell_state(start).is_some() {
+            self.shape = Some(ShapeDrag { start, alive: !erase });
         }
     }
 
-    
+    /// Cell under a screen position, negative when it is before the grid
+    fn cell_at(&self, position: ScreenPosition) -> [isize; 2] {
+        let [x, y] = screen_to_world(position, &self.camera);
+        [x.floor() as isize, y.floor() as isize]
+    }
+
+    /// `None` outside the grid
+    fn cell_state(&self, [x, y]: [isize; 2]) -> Option<bool> {
+        if x < 0 || y < 0 {None} else {self.board.get(y as usize, x as usize)}
+    }
+
+    /// What the preview of the dragged shape, or of a flood fill at the cursor, depends on
+    fn preview_key(&self) -> Option<PreviewKey> {
+        if self.ghost.is_some() || self.browser.is_some() {
+            return None;
+        }
+        let shape = match self.tool {
+            Tool::Fill => None,
+            tool if tool.is_shape() => {
+                let shape = self.shape.as_ref()?;
+                Some((shape.start, shape.alive))
+            },
+            _ => return None
+        };
+        Some(PreviewKey {
+            tool: self.tool,
+            cursor: self.cell_at(self.mouse_coords?),
+            shape,
+            snap: self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)),
+            revision: self.board.revision(),
+        })
+    }
+
+    /// Compute the preview again only when what it depends on changed
+    fn update_preview(&mut self) {
+        let key = self.preview_key();
+        if self.preview.as_ref().map(|preview| preview.key) != key {
+            self.preview = key.and_then(|key| self.compute_preview(key));
+        }
+    }
+
+    /// Cells that the shape or the flood fill would paint, and their new state
+    fn compute_preview(&self, key: PreviewKey) -> Option<Preview> {
+        let cursor = key.cursor;
+        let (cells, alive) = match (key.tool, key.shape) {
+            (Tool::Fill, _) => {
+                let alive = !self.cell_state(cursor)?;
+                (shapes::flood_fill(&self.board, cursor), alive)
+            },
+            (tool, Some((start, alive))) => {
+                let corner = if key.snap {shapes::square_corner(start, cursor)} else {cursor};
+                let cells = match tool {
+                    Tool::Line => shapes::line(start, if key.snap {shapes::snap_45(start, cursor)} else {cursor}),
+                    Tool::Rectangle { filled } => shapes::rectangle(start, corner, filled),
+                    Tool::Ellipse { filled } => shapes::ellipse(start, corner, filled),
+                    _ => return None
+                };
+                (cells, alive)
+            },
+            _ => return None
+        };
+
+        // Kept in their bounding box, inside the board
+        let cells: Vec<[usize; 2]> = cells.into_iter()
+            .filter(|&cell| self.cell_state(cell).is_some())
+            .map(|[x, y]| [x as usize, y as usize])
+            .collect();
+        let left = cells.iter().map(|cell| cell[0]).min()?;
+        let top = cells.iter().map(|cell| cell[1]).min()?;
+        let right = cells.iter().map(|cell| cell[0]).max()?;
+        let bottom = cells.iter().map(|cell| cell[1]).max()?;
+        let mut mask = GameGrid::new(bottom - top + 1, right - left + 1);
+        for [x, y] in cells {
+            let _ = mask.set_state_at(y - top, x - left, true);
+        }
+        Some(Preview { key, cells: mask, origin: [left, top], alive })
+    }
+
+    /// Paint the previewed cells on the board
+    fn apply_shape(&mut self) {
+        self.update_preview();
+        if let Some(preview) = &self.preview {
+            let [left, top] = preview.origin;
+            let cells = preview.cells.alive_cells().map(|(row, col)| (top + row, left + col)).collect();
+            let alive = preview.alive;
+            self.paint(Edit { cells, alive });
+        }
+        self.shape = None;
+    }
+
+    /// Pressing the key of the current shape again switches between hollow and filled
+    fn select_shape_tool(&mut self, tool: Tool) {
+        self.tool = match (self.tool, tool) {
+            (Tool::Rectangle { filled }, Tool::Rectangle { .. }) => Tool::Rectangle { filled: !filled },
+            (Tool::Ellipse { filled }, Tool::Ellipse { .. }) => Tool::Ellipse { filled: !filled },
+            _ => tool
+        };
+        self.shape = None;
+    }
+
+    fn next_brush(&mut self, backward: bool) {
+        self.brush = if backward {(self.brush + BRUSHES.len() - 1) % BRUSHES.len()} else {(self.brush + 1) % BRUSHES.len()};
+    }
+
 
-    pub fn handle_button_press(&mut self, button: Button) { // Only when STARTING to press
+    /// Show the board at another generation of the timeline, pausing the game
+    fn seek(&mut self, generation: u64) {
+        self.stop_running_until();
+        if self.state == GameState::Play {
+            self.switch_pause();
+        }
+        self.cancel_ghost();
+        self.board = self.timeline.seek(generation);
+        self.edit_start = None; // Travelling in time is not an edit
+    }
+
+    /// Recorded as a run so that it can be undone
+    fn step_forward(&mut self) {
+        if self.state != GameState::Pause {
+            return;
+        }
+        self.stop_running_until();
+        self.history.begin_run(&self.board, self.timeline.generation());
+        self.board.next_generation();
+        self.timeline.advance(&self.board);
+        self.history.end_run(&self.board, self.timeline.generation());
+    }
+
+    fn step_backward(&mut self, count: u64) {
+        if self.state == GameState::Pause && self.timeline.generation() > 0 {
+            self.seek(self.timeline.generation().saturating_sub(count));
+        }
+    }
+
+    /// Reached over several frames while staying in pause, earlier generations come from the timeline
+    fn run_until(&mut self, generation: u64) {
+        if self.state == GameState::Play {
+            self.switch_pause();
+        }
+        self.cancel_ghost();
+        if generation <= self.timeline.generation() {
+            self.seek(generation);
+            return;
+        }
+        self.stop_running_until();
+        self.history.begin_run(&self.board, self.timeline.generation());
+        self.target_generation = Some(generation);
+    }
+
+    /// The generations run so far can be undone as a whole
+    fn stop_running_until(&mut self) {
+        if self.target_generation.take().is_some() {
+            self.history.end_run(&self.board, self.timeline.generation());
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: Key) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+        if prompt.kind == PromptKind::Restore {
+            match key {
+                Key::Y | Key::Return => {
+                    self.prompt = None;
+                    self.restore_last_session();
+                },
+                Key::N | Key::Escape => self.prompt = None,
+                _ => {}
+            }
+            return;
+        }
+        match key {
+            Key::Escape => self.prompt = None,
+            Key::Backspace => {prompt.text.pop();},
+            Key::Return => {
+                let Some(Prompt { kind, text }) = self.prompt.take() else {
+                    return;
+                };
+                match kind {
+                    PromptKind::Generation => if let Ok(generation) = text.parse() {
+                        self.run_until(generation);
+                    },
+                    PromptKind::Coordinate => match text.split_once(',').map(|(x, y)| (x.parse::<f64>(), y.parse::<f64>())) {
+                        Some((Ok(x), Ok(y))) => self.go_to([x + 0.5, y + 0.5]),
+                        _ => self.show_message(format!("Expected x,y but got {:?}", text))
+                    },
+                    PromptKind::Restore => {}
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Center the cell, keeping the zoom
+    fn go_to(&mut self, position: WorldPosition) {
+        self.follow = None;
+        let [_, _, width, _] = self.camera.view();
+        self.camera.fly_eased(position, width);
+    }
+
+    fn save_bookmark(&mut self, index: usize) {
+        self.bookmarks.set(index, self.camera.view());
+        self.show_message(format!("Bookmark {} saved", index));
+    }
+
+    fn recall_bookmark(&mut self, index: usize) {
+        match self.bookmarks.get(index) {
+            Some([x, y, width, height]) => {
+                self.follow = None;
+                self.camera.fly_eased([x + width / 2.0, y + height / 2.0], width);
+            },
+            None => self.show_message(format!("No bookmark {}, Ctrl+{} saves the view", index, index))
+        }
+    }
+
+    /// Every change of the board made by the button is recorded for undoing
+    pub fn handle_button_press(&mut self, button: Button) {
+        self.press_button(button);
+        self.end_edit();
+    }
+
+    pub fn handle_button_release(&mut self, button: Button) {
+        self.release_button(button);
+        self.end_edit();
+    }
+
+    fn press_button(&mut self, button: Button) { // Only when STARTING to press
+        if self.browser.is_some() || self.prompt.is_some() {
+            return;
+        }
+        if let Button::Keyboard(key) = button {
+            if self.ctrl() && !matches!(key, Key::LCtrl | Key::RCtrl | Key::LShift | Key::LAlt) {
+                self.chords.insert(key);
+            }
+        }
+        // Arrows move the selection instead of the camera
+        if let (Button::Keyboard(key), Some(_), GameState::Pause, None) = (button, self.selection, self.state, &self.ghost) {
+            match key {
+                Key::Up => return self.nudge_selection(0, -1),
+                Key::Down => return self.nudge_selection(0, 1),
+                Key::Right => return self.nudge_selection(1, 0),
+                Key::Left => return self.nudge_selection(-1, 0),
+                _ => {}
+            }
+        }
         match button {
             Button::Keyboard(key) => {
                 match key {
@@ -271,15 +1461,52 @@ impl Game {
                     Key::Right => {self.pressed_buttons.insert(Button::Keyboard(Key::Right));},
                     Key::Left => {self.pressed_buttons.insert(Button::Keyboard(Key::Left));},
                     Key::LShift => {self.pressed_buttons.insert(Button::Keyboard(Key::LShift));},
+                    Key::Space => {self.pressed_buttons.insert(Button::Keyboard(Key::Space));},
+                    Key::LCtrl => {self.pressed_buttons.insert(Button::Keyboard(Key::LCtrl));},
+                    Key::RCtrl => {self.pressed_buttons.insert(Button::Keyboard(Key::RCtrl));},
+                    Key::LAlt => {self.pressed_buttons.insert(Button::Keyboard(Key::LAlt));},
                     _ => {}
                 }
             },
             Button::Mouse(mouse_button) => {
                 match mouse_button {
+                    MouseButton::Middle => self.start_pan(),
+                    MouseButton::Left if self.pressed_buttons.contains(&Button::Keyboard(Key::Space)) => {
+                        self.space_panned = true;
+                        self.start_pan();
+                    },
+                    MouseButton::Left if self.on_minimap() => {
+                        self.minimap_dragging = true;
+                        if let Some(position) = self.mouse_coords {
+                            self.center_from_minimap(position);
+                        }
+                    },
+                    MouseButton::Left if self.on_scrubber() => {
+                        self.scrubbing = true;
+                        if let Some(position) = self.mouse_coords {
+                            self.update_mouse_position(position);
+                        }
+                    },
+                    MouseButton::Left if self.ghost.is_some() => self.place_ghost(),
                     MouseButton::Left => {
                         self.pressed_buttons.insert(Button::Mouse(MouseButton::Left));
                         if let Some(mouse_position) = self.mouse_coords {
-                            self.edit_at_position(mouse_position);
+                            match self.tool {
+                                Tool::Draw => self.start_stroke(mouse_position, false),
+                                Tool::Select => self.press_selection(mouse_position),
+                                Tool::Fill => self.apply_shape(),
+                                _ => self.start_shape(mouse_position, false)
+                            }
+                        }
+                    },
+                    MouseButton::Right if self.ghost.is_none() => {
+                        self.pressed_buttons.insert(Button::Mouse(MouseButton::Right));
+                        if let Some(mouse_position) = self.mouse_coords {
+                            match self.tool {
+                                Tool::Draw => self.start_stroke(mouse_position, true),
+                                tool if tool.is_shape() => self.start_shape(mouse_position, true),
+                                _ => {}
+                            }
                         }
                     },
                     _ => {}
@@ -289,30 +1516,136 @@ impl Game {
         }
     }
 
-    pub fn handle_button_release(&mut self, button: Button) {
+    fn release_button(&mut self, button: Button) {
+        // Even when Ctrl was let go first
+        let chord = if let Button::Keyboard(key) = button {self.chords.remove(&key)} else {false};
+        if let (Some(_), Button::Keyboard(key)) = (&self.browser, button) {
+            self.pressed_buttons.remove(&button);
+            self.handle_browser_key(key);
+            return;
+        }
+        if let (Some(_), Button::Keyboard(key)) = (&self.prompt, button) {
+            self.pressed_buttons.remove(&button);
+            self.handle_prompt_key(key);
+            return;
+        }
+        let digits = [Key::D0, Key::D1, Key::D2, Key::D3, Key::D4, Key::D5, Key::D6, Key::D7, Key::D8, Key::D9];
+        if let Button::Keyboard(key) = button {
+            if let Some(index) = digits.iter().take(BOOKMARK_COUNT).position(|&digit| digit == key) {
+                return if chord {self.save_bookmark(index)} else {self.recall_bookmark(index)};
+            }
+        }
         match button {
             Button::Keyboard(key) => {
                 match key {
-                    Key::P => self.increase_updates(),
-                    Key::O => self.decrease_updates(),
-                    Key::Space => self.switch_pause(),
+                    Key::P if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => self.change_step_exponent(true),
+                    Key::O if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => self.change_step_exponent(false),
+                    Key::P => self.increase_rate(),
+                    Key::O => self.decrease_rate(),
+                    Key::U => self.switch_hyperspeed(),
+                    Key::Space => {
+                        self.pressed_buttons.remove(&Button::Keyboard(Key::Space));
+                        if !self.space_panned {
+                            self.switch_pause();
+                        }
+                        self.space_panned = false;
+                    },
+                    Key::S if chord => {
+                        self.pressed_buttons.remove(&Button::Keyboard(Key::S));
+                        self.save_selection();
+                    },
+                    Key::X if chord => {
+                        self.pressed_buttons.remove(&Button::Keyboard(Key::X));
+                        self.cut_selection();
+                    },
+                    Key::C if chord => self.copy_selection(),
+                    Key::Z if chord && self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => self.redo(),
+                    Key::Z if chord => self.undo(),
+                    Key::Y if chord => self.redo(),
+                    Key::V if chord => self.paste_clipboard(),
                     Key::S => {self.pressed_buttons.remove(&Button::Keyboard(Key::S));},
                     Key::X => {self.pressed_buttons.remove(&Button::Keyboard(Key::X));},
                     Key::C => if self.state == GameState::Pause {self.clear()},
-                    // Key::G => self.load_glider_canon(),
+                    Key::Delete => self.delete_selection(),
+                    Key::R if chord => self.transform_selection(|pattern| pattern.rotated().rotated()),
+                    Key::R if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => {
+                        self.transform_selection(|pattern| pattern.rotated().rotated().rotated())
+                    },
+                    Key::R => self.transform_selection(GameGrid::rotated),
+                    Key::H => self.transform_selection(GameGrid::flipped_horizontally),
+                    Key::V => self.transform_selection(GameGrid::flipped_vertically),
+                    Key::J => self.transform_selection(GameGrid::transposed),
+                    Key::I => self.invert_selection(),
+                    Key::N => self.fill_selection_randomly(),
+                    Key::K => self.clear_outside_selection(),
+                    Key::Comma if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => self.step_backward(self.step_count),
+                    Key::Comma => self.step_backward(1),
+                    Key::Period if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => {
+                        self.run_until(self.timeline.generation() + self.step_count)
+                    },
+                    Key::Period => self.step_forward(),
+                    Key::G if chord => self.prompt = Some(Prompt { kind: PromptKind::Coordinate, text: String::new() }),
+                    Key::G => self.prompt = Some(Prompt { kind: PromptKind::Generation, text: String::new() }),
+                    Key::D => self.tool = Tool::Draw,
+                    Key::W => self.select_shape_tool(Tool::Line),
+                    Key::Q => self.select_shape_tool(Tool::Rectangle { filled: false }),
+                    Key::E => self.select_shape_tool(Tool::Ellipse { filled: false }),
+                    Key::F => self.tool = Tool::Fill,
+                    Key::LeftBracket => self.next_brush(true),
+                    Key::RightBracket => self.next_brush(false),
+                    Key::M => self.tool = Tool::Select,
+                    Key::L => if self.ghost.is_some() {self.cancel_ghost()} else {self.next_library_pattern(false)},
+                    Key::Tab if self.ghost.as_ref().is_some_and(|ghost| ghost.library_index.is_some()) => {
+                        self.next_library_pattern(self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)))
+                    },
+                    Key::Escape => {
+                        if self.ghost.is_none() {
+                            self.selection = None;
+                        }
+                        self.cancel_ghost();
+                    },
+                    Key::B => self.open_browser(),
+                    Key::F12 => self.take_screenshot(),
+                    Key::F11 => self.export_svg(),
+                    Key::T => self.next_theme(),
+                    Key::F10 => self.show_minimap = !self.show_minimap,
+                    Key::A if self.pressed_buttons.contains(&Button::Keyboard(Key::LShift)) => self.fit_selection(),
+                    Key::A => self.fit_pattern(),
+                    Key::Y => self.switch_follow(self.pressed_buttons.contains(&Button::Keyboard(Key::LShift))),
                     Key::Up => {self.pressed_buttons.remove(&Button::Keyboard(Key::Up));},
                     Key::Down => {self.pressed_buttons.remove(&Button::Keyboard(Key::Down));},
                     Key::Right => {self.pressed_buttons.remove(&Button::Keyboard(Key::Right));},
                     Key::Left => {self.pressed_buttons.remove(&Button::Keyboard(Key::Left));},
                     Key::LShift => {self.pressed_buttons.remove(&Button::Keyboard(Key::LShift));},
+                    Key::LCtrl => {self.pressed_buttons.remove(&Button::Keyboard(Key::LCtrl));},
+                    Key::RCtrl => {self.pressed_buttons.remove(&Button::Keyboard(Key::RCtrl));},
+                    Key::LAlt => {self.pressed_buttons.remove(&Button::Keyboard(Key::LAlt));},
                     _ => {},
                 }
             },
             Button::Mouse(mouse_button) => {
                 match mouse_button {
+                    MouseButton::Middle => self.end_pan(),
+                    MouseButton::Left if self.pan.is_some() => self.end_pan(),
                     MouseButton::Left => {
                         self.pressed_buttons.remove(&Button::Mouse(MouseButton::Left));
-                        self.changed_tiles.clear();
+                        self.stroke = None;
+                        self.scrubbing = false;
+                        self.minimap_dragging = false;
+                        if self.shape.as_ref().is_some_and(|shape| shape.alive) {
+                            self.apply_shape();
+                        }
+                        self.selection_start = None;
+                        if self.ghost.as_ref().is_some_and(|ghost| ghost.moving) {
+                            self.place_ghost();
+                        }
+                    },
+                    MouseButton::Right => {
+                        self.pressed_buttons.remove(&Button::Mouse(MouseButton::Right));
+                        self.stroke = None;
+                        if self.shape.as_ref().is_some_and(|shape| !shape.alive) {
+                            self.apply_shape();
+                        }
                     },
                     _ => {}
                 }
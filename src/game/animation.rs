@@ -0,0 +1,77 @@
+//! Animated GIF of a range of generations, rendered offscreen
+//!
+//! The generations are run twice rather than kept: once to find the cells
+//! shown, then again to encode each frame as soon as it is computed.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::positions::GridRect;
+use super::raster;
+use crate::cli::{Crop, ExportOptions, GifOptions};
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufWriter;
+
+/// Board of the generation `options.from`
+fn first_frame(mut board: GameGrid, options: &GifOptions) -> GameGrid {
+    for _ in 0..options.from {
+        board.next_generation();
+    }
+    board
+}
+
+/// Cells shown in every frame, the bounds of all the frames are found without keeping them
+fn crop_region(first: &GameGrid, export: &ExportOptions, options: &GifOptions) -> GridRect {
+    match options.crop {
+        Crop::View => Camera::new(export.size).visible_cells(),
+        Crop::Bounds => {
+            let mut board = first.clone();
+            let mut bounds = board.bounding_box();
+            for _ in options.from..options.to {
+                board.next_generation();
+                bounds = match (bounds, board.bounding_box()) {
+                    (Some(bounds), Some(frame)) => Some(bounds.union(frame)),
+                    (bounds, frame) => bounds.or(frame)
+                };
+            }
+            bounds.unwrap_or(GridRect { x: 0, y: 0, width: 1, height: 1 })
+        }
+    }
+}
+
+/// ## Errors
+/// If the image is too large for a GIF or can't be written
+pub fn write_gif(board: GameGrid, export: &ExportOptions, options: &GifOptions) -> Result<(), String> {
+    let mut board = first_frame(board, options);
+    let region = crop_region(&board, export, options);
+
+    let too_large = || format!("{}x{} cells of {} pixels are too large for a GIF", region.width, region.height, export.cell_size);
+    let width = u16::try_from(region.width * export.cell_size).map_err(|_| too_large())?;
+    let height = u16::try_from(region.height * export.cell_size).map_err(|_| too_large())?;
+
+    let path = &export.output;
+    let io_error = |e: gif::EncodingError| format!("{}: {}", path.display(), e);
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &export.theme.to_rgb8_palette())
+        .map_err(io_error)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(io_error)?;
+
+    // GIF delays are in hundredths of a second
+    let delay = u16::try_from(options.delay_ms.div_ceil(10)).unwrap_or(u16::MAX);
+    for generation in options.from..=options.to {
+        if generation > options.from {
+            board.next_generation();
+        }
+        let canvas = raster::render_region(&board, region, export.cell_size, &export.theme);
+        let frame = gif::Frame {
+            width,
+            height,
+            delay,
+            buffer: Cow::Owned(canvas.to_indexed(&export.theme)),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(io_error)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Periodic saves of the board and crash recovery
+//!
+//! Snapshots are RLE files rotated in the state directory, `autosave-0.rle`
+//! being the most recent one. A lock file lives as long as a session does,
+//! if it is still there at launch the previous session did not exit cleanly.
+
+use super::grid::GameGrid;
+use super::rle;
+use crate::consts::{AUTOSAVE_INTERVAL, AUTOSAVE_SNAPSHOTS, STATE_DIR_NAME};
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+
+const LOCK_FILE: &str = "session.lock";
+
+pub struct Autosave {
+    dir: PathBuf,
+    /// Seconds since the last save
+    elapsed: f64,
+    /// Encoded board of the last save, nothing is written if it did not change
+    last_saved: String,
+    writer: Option<JoinHandle<io::Result<()>>>,
+}
+
+/// Local directory where the state of the application is kept
+///
+/// `$XDG_STATE_HOME`, `~/.local/state` or `%LOCALAPPDATA%`, the working
+/// directory being the last resort
+pub fn state_dir() -> PathBuf {
+    let base = env::var_os("XDG_STATE_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state")))
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("."));
+    base.join(STATE_DIR_NAME)
+}
+
+fn snapshot_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("autosave-{}.rle", index))
+}
+
+impl Autosave {
+    pub fn open() -> Self {
+        let dir = state_dir();
+        if let Err(error) = fs::create_dir_all(&dir) {
+            eprintln!("Autosave disabled, could not create {}: {}", dir.display(), error);
+        }
+        Autosave { dir, elapsed: 0.0, last_saved: String::new(), writer: None }
+    }
+
+    /// Did the previous session end without calling `end_session` ?
+    pub fn previous_session_crashed(&self) -> bool {
+        self.dir.join(LOCK_FILE).exists() && snapshot_path(&self.dir, 0).exists()
+    }
+
+    /// Most recent snapshot that can still be read
+    pub fn latest(&self) -> Option<GameGrid> {
+        (0..AUTOSAVE_SNAPSHOTS).find_map(|index| {
+            let text = fs::read_to_string(snapshot_path(&self.dir, index)).ok()?;
+            rle::decode(&text).ok()
+        })
+    }
+
+    pub fn begin_session(&mut self) {
+        let _ = fs::write(self.dir.join(LOCK_FILE), std::process::id().to_string());
+    }
+
+    /// Save one last time and mark the session as cleanly exited
+    pub fn end_session(&mut self, board: &GameGrid) {
+        self.save(board);
+        self.wait_writer();
+        let _ = fs::remove_file(self.dir.join(LOCK_FILE));
+    }
+
+    /// Save the board every `AUTOSAVE_INTERVAL` seconds
+    pub fn update(&mut self, dt: f64, board: &GameGrid) {
+        self.elapsed += dt;
+        if self.elapsed >= AUTOSAVE_INTERVAL {
+            self.elapsed = 0.0;
+            self.save(board);
+        }
+    }
+
+    /// Encoding is done here, rotating and writing the files in the background
+    fn save(&mut self, board: &GameGrid) {
+        let text = rle::encode(board);
+        if text == self.last_saved {
+            return;
+        }
+        self.wait_writer();
+        self.last_saved = text.clone();
+        let dir = self.dir.clone();
+        self.writer = Some(thread::spawn(move || write_snapshot(&dir, &text)));
+    }
+
+    fn wait_writer(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            if let Ok(Err(error)) = writer.join() {
+                eprintln!("Autosave failed: {}", error);
+            }
+        }
+    }
+}
+
+fn write_snapshot(dir: &Path, text: &str) -> io::Result<()> {
+    for index in (1..AUTOSAVE_SNAPSHOTS).rev() {
+        let older = snapshot_path(dir, index - 1);
+        if older.exists() {
+            fs::rename(older, snapshot_path(dir, index))?;
+        }
+    }
+    // Written aside then renamed so that a crash while writing can't corrupt it
+    let temporary = dir.join("autosave.tmp");
+    fs::write(&temporary, text)?;
+    fs::rename(temporary, snapshot_path(dir, 0))
+}
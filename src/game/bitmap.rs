@@ -0,0 +1,267 @@
+//! Bitmap images turned into cells
+//!
+//! Reads PNG and the Netpbm formats (PBM, PGM, PPM, ascii or binary).
+//! Dark pixels become alive cells.
+
+use super::grid::GameGrid;
+use crate::consts::MAX_PATTERN_CELLS;
+
+use std::fs;
+use std::path::Path;
+
+/// Extensions that are read as images rather than patterns
+pub const IMAGE_EXTENSIONS: [&str; 5] = ["png", "pbm", "pgm", "ppm", "pnm"];
+
+#[derive(Clone, Copy)]
+pub struct ImportOptions {
+    /// Pixels darker than it are alive, between 0 and 1
+    pub threshold: f32,
+    /// Floyd–Steinberg dithering, to keep the shades of photos
+    pub dither: bool,
+    /// Number of cells per pixel along each axis
+    pub scale: f64,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions { threshold: 0.5, dither: false, scale: 1.0 }
+    }
+}
+
+/// Grayscale image, each luminance being between 0 (black) and 1 (white)
+struct Bitmap {
+    width: usize,
+    height: usize,
+    luminance: Vec<f32>,
+}
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// ## Errors
+/// If the file can't be read, is not a supported image, or gives more than `MAX_PATTERN_CELLS`
+pub fn import(path: &Path, options: &ImportOptions) -> Result<GameGrid, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let bitmap = if bytes.starts_with(b"\x89PNG") {
+        decode_png(&bytes)
+    }
+    else if bytes.first() == Some(&b'P') {
+        decode_netpbm(&bytes)
+    }
+    else {
+        Err(String::from("Unsupported image format"))
+    };
+    let bitmap = bitmap.map_err(|e| format!("{}: {}", path.display(), e))?;
+    if bitmap.width == 0 || bitmap.height == 0 {
+        return Err(format!("{}: Empty image", path.display()));
+    }
+    let cells = (bitmap.width as f64 * options.scale).round() * (bitmap.height as f64 * options.scale).round();
+    if cells > MAX_PATTERN_CELLS as f64 {
+        return Err(format!("{}: The pattern would be larger than {} cells", path.display(), MAX_PATTERN_CELLS));
+    }
+    Ok(to_cells(&scale(&bitmap, options.scale), options))
+}
+
+/// Rec. 709 luma
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn decode_png(bytes: &[u8]) -> Result<Bitmap, String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+
+    let channels = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+    let mut luminance = Vec::with_capacity(width * height);
+    for row in buffer.chunks(info.line_size).take(height) {
+        for pixel in row.chunks(channels).take(width) {
+            let value = |i: usize| pixel[i] as f32 / 255.0;
+            let (color, alpha) = match pixel.len() {
+                1 => (value(0), 1.0),
+                2 => (value(0), value(1)),
+                3 => (luma(value(0), value(1), value(2)), 1.0),
+                _ => (luma(value(0), value(1), value(2)), value(3)),
+            };
+            // Transparent pixels are seen as white, hence dead
+            luminance.push(color * alpha + (1.0 - alpha));
+        }
+    }
+    Ok(Bitmap { width, height, luminance })
+}
+
+/// Next whitespace separated token of a Netpbm header, skipping comments
+fn next_token(bytes: &[u8], position: &mut usize) -> Result<usize, String> {
+    loop {
+        while *position < bytes.len() && bytes[*position].is_ascii_whitespace() {
+            *position += 1;
+        }
+        if bytes.get(*position) != Some(&b'#') {
+            break;
+        }
+        while *position < bytes.len() && bytes[*position] != b'\n' {
+            *position += 1;
+        }
+    }
+    let start = *position;
+    while *position < bytes.len() && bytes[*position].is_ascii_digit() {
+        *position += 1;
+    }
+    std::str::from_utf8(&bytes[start..*position]).ok()
+        .and_then(|token| token.parse().ok())
+        .ok_or_else(|| String::from("Truncated or malformed Netpbm file"))
+}
+
+fn decode_netpbm(bytes: &[u8]) -> Result<Bitmap, String> {
+    let kind = bytes.get(1).copied().unwrap_or(0);
+    if !(b'1'..=b'6').contains(&kind) {
+        return Err(String::from("Unsupported Netpbm format"));
+    }
+    let mut position = 2;
+    let width = next_token(bytes, &mut position)?;
+    let height = next_token(bytes, &mut position)?;
+    let max = if kind == b'1' || kind == b'4' {1} else {next_token(bytes, &mut position)?.max(1)};
+    let channels = if kind == b'3' || kind == b'6' {3} else {1};
+    // Even a packed PBM has a bit per pixel, a header asking for more is not allocated
+    if width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels)).is_none_or(|samples| samples > bytes.len() * 8) {
+        return Err(String::from("Truncated Netpbm file"));
+    }
+
+    let samples: Vec<f32> = match kind {
+        b'1' => { // Digits may not be separated
+            bytes[position..].iter()
+                .filter(|byte| byte.is_ascii_digit())
+                .take(width * height)
+                .map(|&byte| if byte == b'1' {0.0} else {1.0})
+                .collect()
+        },
+        b'2' | b'3' => {
+            let mut samples = Vec::with_capacity(width * height * channels);
+            for _ in 0..width * height * channels {
+                samples.push(next_token(bytes, &mut position)? as f32 / max as f32);
+            }
+            samples
+        },
+        b'4' => { // One bit per pixel, rows padded to a byte
+            let data = bytes.get(position + 1..).unwrap_or(&[]);
+            let row_bytes = width.div_ceil(8);
+            (0..height).flat_map(|row| (0..width).map(move |col| (row, col)))
+                .map(|(row, col)| {
+                    let byte = data.get(row * row_bytes + col / 8).copied().unwrap_or(0);
+                    if byte & (0x80 >> (col % 8)) != 0 {0.0} else {1.0}
+                })
+                .collect()
+        },
+        _ => { // One or two bytes per sample
+            let data = bytes.get(position + 1..).unwrap_or(&[]);
+            let sample_bytes = if max > 255 {2} else {1};
+            data.chunks(sample_bytes)
+                .take(width * height * channels)
+                .map(|sample| sample.iter().fold(0, |value, &byte| value * 256 + byte as usize) as f32 / max as f32)
+                .collect()
+        }
+    };
+    if samples.len() < width * height * channels {
+        return Err(String::from("Truncated Netpbm file"));
+    }
+
+    let luminance = if channels == 3 {
+        samples.chunks(3).map(|rgb| luma(rgb[0], rgb[1], rgb[2])).collect()
+    }
+    else {
+        samples
+    };
+    Ok(Bitmap { width, height, luminance })
+}
+
+/// Box filter when shrinking, nearest neighbor when enlarging
+fn scale(bitmap: &Bitmap, factor: f64) -> Bitmap {
+    let width = ((bitmap.width as f64 * factor).round() as usize).max(1);
+    let height = ((bitmap.height as f64 * factor).round() as usize).max(1);
+    let mut luminance = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let y_start = y * bitmap.height / height;
+        let y_end = ((y + 1) * bitmap.height / height).max(y_start + 1);
+        for x in 0..width {
+            let x_start = x * bitmap.width / width;
+            let x_end = ((x + 1) * bitmap.width / width).max(x_start + 1);
+            let mut sum = 0.0;
+            for source_y in y_start..y_end {
+                for source_x in x_start..x_end {
+                    sum += bitmap.luminance[source_y * bitmap.width + source_x];
+                }
+            }
+            luminance.push(sum / ((y_end - y_start) * (x_end - x_start)) as f32);
+        }
+    }
+    Bitmap { width, height, luminance }
+}
+
+fn to_cells(bitmap: &Bitmap, options: &ImportOptions) -> GameGrid {
+    let mut grid = GameGrid::new(bitmap.height, bitmap.width);
+    let mut luminance = bitmap.luminance.clone();
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            let old = luminance[y * bitmap.width + x];
+            let alive = old < options.threshold;
+            if alive {
+                let _ = grid.set_state_at(y, x, true);
+            }
+            if options.dither {
+                // Spread the error on the pixels that are not processed yet
+                let error = old - if alive {0.0} else {1.0};
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    if nx >= 0 && (nx as usize) < bitmap.width && y + dy < bitmap.height {
+                        luminance[(y + dy) * bitmap.width + nx as usize] += error * weight;
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dark_pixels_are_alive() {
+        let bitmap = decode_netpbm(b"P2\n3 2\n255\n0 255 10\n255 128 200\n").unwrap();
+        let grid = to_cells(&bitmap, &ImportOptions::default());
+        assert_eq!(grid.alive_cells().collect::<Vec<_>>(), vec![(0, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn reads_packed_pbm() {
+        let bitmap = decode_netpbm(b"P4\n3 2\n\xa0\x40").unwrap();
+        let grid = to_cells(&bitmap, &ImportOptions::default());
+        assert_eq!(grid.alive_cells().collect::<Vec<_>>(), vec![(0, 0), (0, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn rejects_headers_larger_than_the_file() {
+        assert!(decode_netpbm(b"P4\n100000 100000\n\x00").is_err());
+        assert!(decode_netpbm(format!("P1\n{} 2\n0", usize::MAX).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn scale_averages_when_shrinking() {
+        let bitmap = Bitmap { width: 2, height: 2, luminance: vec![0.0, 1.0, 1.0, 1.0] };
+        let scaled = scale(&bitmap, 0.5);
+        assert_eq!((scaled.width, scaled.height), (1, 1));
+        assert_eq!(scaled.luminance, vec![0.75]);
+        assert_eq!(scale(&bitmap, 2.0).luminance.len(), 16);
+    }
+}
@@ -0,0 +1,54 @@
+//! Numbered camera views, kept in the state directory across sessions
+
+use super::autosave::state_dir;
+
+use std::fs;
+
+const BOOKMARKS_FILE: &str = "bookmarks.tsv";
+
+/// One per digit key
+pub const BOOKMARK_COUNT: usize = 10;
+
+/// `[x, y, width, height]` of the camera
+pub type View = [f64; 4];
+
+pub struct Bookmarks {
+    views: [Option<View>; BOOKMARK_COUNT],
+}
+
+impl Bookmarks {
+    /// Bookmarks of the previous sessions, if any
+    pub fn load() -> Self {
+        let mut views = [None; BOOKMARK_COUNT];
+        let text = fs::read_to_string(state_dir().join(BOOKMARKS_FILE)).unwrap_or_default();
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [index, x, y, width, height] = fields[..] else {
+                continue;
+            };
+            let (Ok(index), Ok(x), Ok(y), Ok(width), Ok(height)) =
+                (index.parse::<usize>(), x.parse(), y.parse(), width.parse(), height.parse()) else {
+                continue;
+            };
+            if index < BOOKMARK_COUNT {
+                views[index] = Some([x, y, width, height]);
+            }
+        }
+        Bookmarks { views }
+    }
+
+    pub fn get(&self, index: usize) -> Option<View> {
+        self.views.get(index).copied().flatten()
+    }
+
+    /// Saved right away, so that a crash does not lose it
+    pub fn set(&mut self, index: usize, view: View) {
+        self.views[index] = Some(view);
+        let text: String = self.views.iter().enumerate()
+            .filter_map(|(index, view)| view.map(|[x, y, width, height]| format!("{}\t{}\t{}\t{}\t{}\n", index, x, y, width, height)))
+            .collect();
+        if let Err(error) = fs::write(state_dir().join(BOOKMARKS_FILE), text) {
+            eprintln!("Could not save the bookmarks: {}", error);
+        }
+    }
+}
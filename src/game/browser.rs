@@ -0,0 +1,99 @@
+//! Overlay to browse the indexed pattern directory
+//!
+//! Patterns are shown as a grid of thumbnails, filtered by the typed text.
+
+use super::pattern_index::IndexEntry;
+use super::theme::Theme;
+use crate::consts::{BROWSER_TILE, BROWSER_TILE_MARGIN};
+
+use graphics::{rectangle, Context};
+use opengl_graphics::GlGraphics;
+
+#[derive(Default)]
+pub struct Browser {
+    filter: String,
+    /// Index among the filtered entries
+    selected: usize,
+}
+
+impl Browser {
+    pub fn filtered<'a>(&self, entries: &'a [IndexEntry]) -> Vec<&'a IndexEntry> {
+        let filter = self.filter.to_lowercase();
+        entries.iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&filter)
+                || entry.path.to_string_lossy().to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub fn selected<'a>(&self, entries: &'a [IndexEntry]) -> Option<&'a IndexEntry> {
+        self.filtered(entries).get(self.selected).copied()
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.filter.push_str(text);
+        self.selected = 0;
+    }
+
+    pub fn erase_char(&mut self) {
+        self.filter.pop();
+        self.selected = 0;
+    }
+
+    /// Move the selection by whole rows or columns of the thumbnail grid
+    pub fn move_selection(&mut self, dx: isize, dy: isize, entries: &[IndexEntry], draw_size: [f64; 2]) {
+        let count = self.filtered(entries).len();
+        if count == 0 {
+            return;
+        }
+        let (columns, _) = layout(draw_size);
+        let target = self.selected as isize + dx + dy * columns as isize;
+        self.selected = target.clamp(0, count as isize - 1) as usize;
+    }
+
+    pub fn title(&self, entries: &[IndexEntry]) -> String {
+        let filtered = self.filtered(entries);
+        match filtered.get(self.selected) {
+            Some(entry) => format!("Patterns [{}] {}/{}: {} - {}x{} - {} (Enter to place, Esc to close)",
+                self.filter, self.selected + 1, filtered.len(), entry.name, entry.width, entry.height, entry.rule),
+            None => format!("Patterns [{}]: nothing matches (Esc to close)", self.filter)
+        }
+    }
+
+    pub fn render(&self, entries: &[IndexEntry], theme: &Theme, draw_size: [f64; 2], context: &Context, gl: &mut GlGraphics) {
+        let [r, g, b, _] = theme.background;
+        rectangle([r, g, b, 0.92], [0.0, 0.0, draw_size[0], draw_size[1]], context.transform, gl);
+
+        let filtered = self.filtered(entries);
+        let (columns, rows) = layout(draw_size);
+        let per_page = columns * rows;
+        let first = self.selected / per_page * per_page;
+        let step = BROWSER_TILE + BROWSER_TILE_MARGIN;
+
+        for (i, entry) in filtered.iter().enumerate().skip(first).take(per_page) {
+            let x = BROWSER_TILE_MARGIN + ((i - first) % columns) as f64 * step;
+            let y = BROWSER_TILE_MARGIN + ((i - first) / columns) as f64 * step;
+            let tile_alpha = if i == self.selected {0.35} else {0.1};
+            rectangle([theme.cell[0], theme.cell[1], theme.cell[2], tile_alpha], [x, y, BROWSER_TILE, BROWSER_TILE], context.transform, gl);
+
+            let (thumbnail_rows, thumbnail_cols) = entry.thumbnail.get_shape();
+            let cell = (BROWSER_TILE - 8.0) / thumbnail_rows.max(thumbnail_cols).max(1) as f64;
+            let offset_x = x + (BROWSER_TILE - cell * thumbnail_cols as f64) / 2.0;
+            let offset_y = y + (BROWSER_TILE - cell * thumbnail_rows as f64) / 2.0;
+            for row in 0..thumbnail_rows {
+                for col in 0..thumbnail_cols {
+                    if entry.thumbnail.get(row, col).unwrap() {
+                        rectangle(theme.cell, [offset_x + col as f64 * cell, offset_y + row as f64 * cell, cell, cell], context.transform, gl);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Number of `(columns, rows)` of thumbnails that fit on the screen
+fn layout(draw_size: [f64; 2]) -> (usize, usize) {
+    let step = BROWSER_TILE + BROWSER_TILE_MARGIN;
+    let columns = ((draw_size[0] - BROWSER_TILE_MARGIN) / step).floor().max(1.0) as usize;
+    let rows = ((draw_size[1] - BROWSER_TILE_MARGIN) / step).floor().max(1.0) as usize;
+    (columns, rows)
+}
@@ -0,0 +1,38 @@
+//! Brushes painting several cells around the cursor
+
+#[derive(Clone, Copy)]
+pub enum Brush {
+    /// Side of the square
+    Square(usize),
+    /// Radius of the disc, the cell under the cursor excluded
+    Disc(usize),
+}
+
+pub const BRUSHES: [Brush; 5] = [Brush::Square(1), Brush::Square(3), Brush::Disc(2), Brush::Disc(4), Brush::Disc(8)];
+
+impl Brush {
+    /// Offsets `[x, y]` of the painted cells from the cell under the cursor
+    pub fn offsets(self) -> Vec<[isize; 2]> {
+        match self {
+            Brush::Square(side) => {
+                let start = -(side as isize / 2);
+                let end = start + side as isize;
+                (start..end).flat_map(|y| (start..end).map(move |x| [x, y])).collect()
+            },
+            Brush::Disc(radius) => {
+                let radius = radius as isize;
+                (-radius..=radius)
+                    .flat_map(|y| (-radius..=radius).map(move |x| [x, y]))
+                    .filter(|[x, y]| x * x + y * y <= radius * radius + radius) // Rounder than the exact disc
+                    .collect()
+            }
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Brush::Square(side) => format!("{}x{}", side, side),
+            Brush::Disc(radius) => format!("disc of radius {}", radius)
+        }
+    }
+}
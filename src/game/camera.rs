@@ -1,6 +1,8 @@
 use crate::consts::{WORLD_WIDTH, WORLD_HEIGHT, SUPER_ZOOM_SPEED, ZOOM_SPEED, DEFAULT_CAMERA_LENGTH};
+use crate::consts::{MAX_ZOOM_LEVEL, MIN_ZOOM_LEVEL, ZOOM_SMOOTHNESS};
+use crate::consts::{CAMERA_SMOOTHNESS, FIT_MARGIN, FLY_DURATION, MIN_FIT_WIDTH};
 
-use super::{ScreenPosition, WorldPosition, DEFAULT_WINDOW_SIZE};
+use super::{GridRect, ScreenPosition, WorldPosition, DEFAULT_WINDOW_SIZE};
 
 pub struct Camera {
     x: f64,
@@ -9,6 +11,27 @@ pub struct Camera {
     height: f64,
     /// Shape of the drawable part of the screen
     draw_size: [f64; 2],
+    /// Zoom level being reached smoothly
+    zoom_target: Option<ZoomTarget>,
+    /// Center and width being reached smoothly
+    flight: Option<Flight>,
+}
+
+struct Flight {
+    center: WorldPosition,
+    width: f64,
+    /// Center and width at the start of an eased flight, that lasts `FLY_DURATION`
+    start: Option<(WorldPosition, f64)>,
+    /// Seconds since the start
+    elapsed: f64,
+}
+
+struct ZoomTarget {
+    /// Log2 of the cell size in pixels
+    level: i32,
+    /// Stays under `screen_anchor` while zooming
+    world_anchor: WorldPosition,
+    screen_anchor: ScreenPosition,
 }
 
 /*
@@ -47,54 +70,84 @@ fn minimumf64(a: f64, b: f64) -> f64 {
 
 impl Default for Camera {
     fn default() -> Self {
+        Camera::new(DEFAULT_WINDOW_SIZE)
+    }
+}
+
+impl Camera {
+    /// Camera centered on the world for a screen of the given shape
+    pub fn new(draw_size: [f64; 2]) -> Self {
         let world_center = [WORLD_WIDTH /2.0, WORLD_HEIGHT /2.0];
         let width = minimumf64(WORLD_WIDTH, DEFAULT_CAMERA_LENGTH);
-        let height = width * DEFAULT_WINDOW_SIZE[1] / DEFAULT_WINDOW_SIZE[0];
+        let height = width * draw_size[1] / draw_size[0];
 
-        Camera {
+        let mut camera = Camera {
             x: world_center[0] - width/2.0,
             y: world_center[1] - height/2.0,
             width,
             height,
-            draw_size: DEFAULT_WINDOW_SIZE
-        }
+            draw_size,
+            zoom_target: None,
+            flight: None
+        };
+        camera.correct_size();
+        camera.correct_position();
+        camera
     }
-}
 
-impl Camera {
+    /// Camera showing exactly the given cells, each being `cell_size` pixels wide
+    ///
+    /// It is not constrained to the world, it is meant for exports
+    pub fn framing(region: GridRect, cell_size: f64) -> Self {
+        Camera {
+            x: region.x as f64,
+            y: region.y as f64,
+            width: region.width as f64,
+            height: region.height as f64,
+            draw_size: [region.width as f64 * cell_size, region.height as f64 * cell_size],
+            zoom_target: None,
+            flight: None
+        }
+    }
 
+    /// Keep the view inside the world, or the world centered in the view when it is larger
     fn correct_position(&mut self) {
-        if self.x + self.width >= WORLD_WIDTH - 0.1 {
-            self.x -= self.x + self.width - WORLD_WIDTH + 0.1;
+        if self.width >= WORLD_WIDTH {
+            self.x = (WORLD_WIDTH - self.width) / 2.0;
         }
-        if self.x.is_sign_negative() {
-            self.x = 0.0;
-        }
-        else if self.x > WORLD_WIDTH {
-            self.x = WORLD_WIDTH;
+        else {
+            if self.x + self.width >= WORLD_WIDTH - 0.1 {
+                self.x -= self.x + self.width - WORLD_WIDTH + 0.1;
+            }
+            if self.x.is_sign_negative() {
+                self.x = 0.0;
+            }
         }
 
-        if self.y + self.height >= WORLD_HEIGHT - 0.1 {
-            self.y -= self.y + self.height - WORLD_HEIGHT + 0.1;
-        }
-        if self.y.is_sign_negative() {
-            self.y = 0.0;
+        if self.height >= WORLD_HEIGHT {
+            self.y = (WORLD_HEIGHT - self.height) / 2.0;
         }
-        else if self.y > WORLD_HEIGHT {
-            self.y = WORLD_HEIGHT;
+        else {
+            if self.y + self.height >= WORLD_HEIGHT - 0.1 {
+                self.y -= self.y + self.height - WORLD_HEIGHT + 0.1;
+            }
+            if self.y.is_sign_negative() {
+                self.y = 0.0;
+            }
         }
     }
 
+    /// At least a cell, at most what fits on the screen with cells of the smallest zoom level
     fn correct_size(&mut self) {
         let mut need_to_check = false;
+        let [max_width, max_height] = self.max_size();
 
         if self.width < 1.0 {
             self.width = 1.0;
             need_to_check = true;
         }
-        else if self.width > WORLD_WIDTH - 0.1 {
-            self.x = 0.0;
-            self.width = WORLD_WIDTH - 0.1;
+        else if self.width > max_width {
+            self.width = max_width;
             need_to_check = true;
         }
 
@@ -102,9 +155,8 @@ impl Camera {
             self.height = 1.0;
             need_to_check = true;
         }
-        else if self.height > WORLD_HEIGHT - 0.1 {
-            self.y = 0.0;
-            self.height = WORLD_HEIGHT - 0.1;
+        else if self.height > max_height {
+            self.height = max_height;
             need_to_check = true;
         }
 
@@ -113,6 +165,12 @@ impl Camera {
         }
     }
 
+    /// Largest view, in `WorldPosition`
+    fn max_size(&self) -> [f64; 2] {
+        let smallest_cell = f64::from(MIN_ZOOM_LEVEL).exp2();
+        [self.draw_size[0] / smallest_cell, self.draw_size[1] / smallest_cell]
+    }
+
     fn correct_dimension(&mut self) {
         let a = self.width * self.draw_size[1];
         let b = self.height * self.draw_size[0];
@@ -127,6 +185,10 @@ impl Camera {
 
     /// Update the shape of the camera to match the screen
     pub fn resize(&mut self, new_draw_size: [f64;2]) {
+        // A minimized window has no size, the view is kept for when it is restored
+        if new_draw_size[0] <= 0.0 || new_draw_size[1] <= 0.0 {
+            return;
+        }
 
         // Camera and screen should have same shape
         self.width *= new_draw_size[0] / self.draw_size[0];
@@ -141,24 +203,20 @@ impl Camera {
         // assert_camera(&self);
     }
 
+    /// Screen position of the cell starting at `position`, if any part of it is on screen
     pub fn world_to_screen(&self, position: WorldPosition) -> Option<ScreenPosition> {
-        let x = (position[0] - self.x) / self.width * self.draw_size[0];
-        let y = (position[1] - self.y) / self.height * self.draw_size[1];
-
-        if (x.is_sign_positive() && x < self.draw_size[0]) || (y.is_sign_positive() && y < self.draw_size[1]) {
-            Some([x,y])
-        }
-        else {
-            let a = (position[0] +1.0 - self.x) / self.width * self.draw_size[0];
-            let b = (position[1] +1.0 - self.y) / self.height * self.draw_size[1];
+        let [x, y] = self.project(position);
+        let cell_length = self.cell_lenght();
+        let visible = x + cell_length > 0.0 && x < self.draw_size[0] && y + cell_length > 0.0 && y < self.draw_size[1];
+        visible.then_some([x, y])
+    }
 
-            if a.is_sign_positive() || b.is_sign_positive() {
-                Some([x,y])
-            }
-            else {
-                None
-            }
-        }
+    /// Screen position of any world position, visible or not
+    pub fn project(&self, position: WorldPosition) -> ScreenPosition {
+        [
+            (position[0] - self.x) / self.width * self.draw_size[0],
+            (position[1] - self.y) / self.height * self.draw_size[1]
+        ]
     }
 
     pub fn screen_to_world(&self, position: ScreenPosition) -> WorldPosition {
@@ -169,6 +227,7 @@ impl Camera {
     }
 
     pub fn move_max(&mut self, transform: [f64;2]) {
+        self.flight = None;
         self.x += transform[0];
         self.y += transform[1];
 
@@ -178,6 +237,8 @@ impl Camera {
     }
 
     pub fn zoom(&mut self, dt: f64, faster: bool) {
+        self.zoom_target = None;
+        self.flight = None;
         if self.width > 1.0 && self.height > 1.0 {
             let epsilon = (1.0 + dt) * if faster {SUPER_ZOOM_SPEED} else {ZOOM_SPEED};
             let new_width = self.width - 2.0*epsilon;
@@ -198,7 +259,9 @@ impl Camera {
     }
 
     pub fn dezoom(&mut self, dt: f64, faster: bool) {
-        if self.width < WORLD_WIDTH - 0.1 && self.height < WORLD_HEIGHT - 0.1 {
+        self.zoom_target = None;
+        self.flight = None;
+        if self.width < self.max_size()[0] {
             let epsilon = (1.0 + dt) * if faster {SUPER_ZOOM_SPEED} else {ZOOM_SPEED};
             let new_width = self.width + 2.0*epsilon;
             let new_height = self.height * new_width / self.width;
@@ -214,6 +277,133 @@ impl Camera {
         }
     }
 
+    /// Go towards the next power of two cell size, in or out, keeping the world
+    /// position under the cursor where it is
+    pub fn wheel_zoom(&mut self, zoom_in: bool, cursor: ScreenPosition) {
+        let level = match &self.zoom_target {
+            Some(target) => target.level,
+            None => self.cell_lenght().log2().round() as i32
+        };
+        let level = (if zoom_in {level + 1} else {level - 1}).clamp(MIN_ZOOM_LEVEL, MAX_ZOOM_LEVEL);
+        self.flight = None;
+        self.zoom_target = Some(ZoomTarget { level, world_anchor: self.screen_to_world(cursor), screen_anchor: cursor });
+    }
+
+    /// Interpolate towards the wheel zoom level
+    pub fn update_zoom(&mut self, dt: f64) {
+        let Some(target) = &self.zoom_target else {
+            return;
+        };
+        let current = self.cell_lenght().log2();
+        let goal = target.level as f64;
+        let mut level = goal + (current - goal) * (-dt * ZOOM_SMOOTHNESS).exp();
+        if (level - goal).abs() < 0.01 {
+            level = goal;
+        }
+
+        let cell_size = level.exp2();
+        self.width = self.draw_size[0] / cell_size;
+        self.height = self.draw_size[1] / cell_size;
+        self.x = target.world_anchor[0] - target.screen_anchor[0] / cell_size;
+        self.y = target.world_anchor[1] - target.screen_anchor[1] / cell_size;
+        self.correct_size();
+        self.correct_position();
+
+        // Reached, or out of the sizes allowed
+        if level == goal || (self.cell_lenght().log2() - level).abs() > 0.01 {
+            self.zoom_target = None;
+        }
+    }
+
+    /// Move smoothly to the given center, and to the given width if any
+    pub fn fly_to(&mut self, center: WorldPosition, width: Option<f64>) {
+        if width.is_some() {
+            self.zoom_target = None;
+        }
+        self.flight = Some(Flight { center, width: width.unwrap_or(self.width), start: None, elapsed: 0.0 });
+    }
+
+    /// Fly to the given center and width, easing in and out
+    pub fn fly_eased(&mut self, center: WorldPosition, width: f64) {
+        self.zoom_target = None;
+        self.flight = Some(Flight { center, width, start: Some((self.center(), self.width)), elapsed: 0.0 });
+    }
+
+    /// `[x, y, width, height]`
+    pub fn view(&self) -> [f64; 4] {
+        [self.x, self.y, self.width, self.height]
+    }
+
+    /// Fly to a view showing the whole region with some margin
+    pub fn fit(&mut self, region: GridRect) {
+        let aspect = self.draw_size[0] / self.draw_size[1];
+        let width = (region.width as f64).max(region.height as f64 * aspect) * (1.0 + 2.0 * FIT_MARGIN);
+        let center = [region.x as f64 + region.width as f64 / 2.0, region.y as f64 + region.height as f64 / 2.0];
+        self.fly_to(center, Some(width.max(MIN_FIT_WIDTH)));
+    }
+
+    /// Interpolate towards the end of the flight
+    pub fn update_flight(&mut self, dt: f64) {
+        let center = self.center();
+        let Some(flight) = &mut self.flight else {
+            return;
+        };
+        // Smoothstep from the start, or exponential approach from where the camera is
+        flight.elapsed += dt;
+        let (from, progress) = match flight.start {
+            Some((start_center, start_width)) => {
+                let t = (flight.elapsed / FLY_DURATION).min(1.0);
+                ((start_center, start_width), t * t * (3.0 - 2.0 * t))
+            },
+            None => ((center, self.width), 1.0 - (-dt * CAMERA_SMOOTHNESS).exp())
+        };
+        let mut new_center = [
+            from.0[0] + (flight.center[0] - from.0[0]) * progress,
+            from.0[1] + (flight.center[1] - from.0[1]) * progress
+        ];
+        // Interpolated on a log scale so that zooming looks steady
+        let mut width = (from.1.ln() + (flight.width.ln() - from.1.ln()) * progress).exp();
+        let arrived = (new_center[0] - flight.center[0]).abs() < 0.01 && (new_center[1] - flight.center[1]).abs() < 0.01
+            && (width - flight.width).abs() < 0.01;
+        if arrived {
+            new_center = flight.center;
+            width = flight.width;
+            self.flight = None;
+        }
+
+        let previous = (center, self.width);
+        self.width = width;
+        self.height = width * self.draw_size[1] / self.draw_size[0];
+        self.x = new_center[0] - self.width / 2.0;
+        self.y = new_center[1] - self.height / 2.0;
+        self.correct_size();
+        self.correct_position();
+
+        // Stuck against the edges of the world
+        let center = self.center();
+        if (center[0] - previous.0[0]).abs() + (center[1] - previous.0[1]).abs() + (self.width - previous.1).abs() < 1e-6 {
+            self.flight = None;
+        }
+    }
+
+    pub fn center(&self) -> WorldPosition {
+        [self.x + self.width / 2.0, self.y + self.height / 2.0]
+    }
+
+    /// Cells that are at least partially visible, clipped to the world
+    pub fn visible_cells(&self) -> GridRect {
+        let x = self.x.max(0.0).floor() as usize;
+        let y = self.y.max(0.0).floor() as usize;
+        let right = (self.x + self.width).ceil().min(WORLD_WIDTH) as usize;
+        let bottom = (self.y + self.height).ceil().min(WORLD_HEIGHT) as usize;
+        GridRect { x, y, width: right.saturating_sub(x), height: bottom.saturating_sub(y) }
+    }
+
+    /// Shape of the drawable part of the screen
+    pub fn draw_size(&self) -> [f64; 2] {
+        self.draw_size
+    }
+
     pub fn cell_lenght(&self) -> f64 {
         self.draw_size[0] / self.width
     }
@@ -0,0 +1,65 @@
+//! Plaintext patterns, e.g. :
+//! ```text
+//! !Name: Glider
+//! .O
+//! ..O
+//! OOO
+//! ```
+
+use super::grid::GameGrid;
+use crate::consts::MAX_PATTERN_CELLS;
+
+/// Name given by a `!Name:` line
+pub fn name(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("!Name:"))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// `O` (or `*`) are alive cells, anything else is dead, `!` starts a comment line
+///
+/// ## Errors
+/// If there is no cell at all, or more than `MAX_PATTERN_CELLS`
+pub fn decode(text: &str) -> Result<GameGrid, String> {
+    let lines: Vec<&str> = text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.starts_with('!'))
+        .collect();
+    let cols = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    if lines.is_empty() || cols == 0 {
+        return Err(String::from("Empty plaintext pattern"));
+    }
+    if lines.len().saturating_mul(cols) > MAX_PATTERN_CELLS {
+        return Err(format!("The {}x{} pattern is larger than {} cells", cols, lines.len(), MAX_PATTERN_CELLS));
+    }
+
+    let mut grid = GameGrid::new(lines.len(), cols);
+    for (row, line) in lines.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            if c == 'O' || c == '*' {
+                let _ = grid.set_state_at(row, col, true);
+            }
+        }
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_plaintext() {
+        let text = "!Name: Glider\n!\n.O\n..O\nOOO\n";
+        assert_eq!(name(text).as_deref(), Some("Glider"));
+        let grid = decode(text).unwrap();
+        assert_eq!(grid.get_shape(), (3, 3));
+        assert_eq!(grid.alive_cells().collect::<Vec<_>>(), vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn rejects_empty_pattern() {
+        assert!(decode("!Name: Nothing\n").is_err());
+    }
+}
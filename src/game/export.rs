@@ -0,0 +1,61 @@
+//! Exports that run without any window
+
+use super::animation;
+use super::autosave::Autosave;
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::raster;
+use super::files;
+use super::svg::{self, SvgOptions};
+use super::positions::GridRect;
+use crate::cli::{ExportOptions, GifOptions};
+use crate::consts::{GRID_HEIGHT, GRID_WIDTH};
+
+use std::fs;
+
+/// Put a pattern at the center of an empty board
+///
+/// A pattern with the shape of the board (like an autosave) is kept in place
+pub fn centered_board(pattern: &GameGrid) -> GameGrid {
+    let mut board = GameGrid::new(GRID_HEIGHT, GRID_WIDTH);
+    let (rows, cols) = pattern.get_shape();
+    board.paste(pattern, (GRID_HEIGHT.saturating_sub(rows)) / 2, (GRID_WIDTH.saturating_sub(cols)) / 2);
+    board
+}
+
+/// The given pattern, or else the last autosave, or else an empty board
+fn initial_board(options: &ExportOptions) -> Result<GameGrid, String> {
+    match &options.pattern {
+        Some(path) => Ok(centered_board(&files::read_pattern(path, &options.image)?)),
+        None => Ok(Autosave::open().latest().unwrap_or_else(|| GameGrid::new(GRID_HEIGHT, GRID_WIDTH)))
+    }
+}
+
+/// ## Errors
+/// If the board can't be loaded or the image can't be written
+pub fn screenshot(options: &ExportOptions) -> Result<(), String> {
+    let board = initial_board(options)?;
+    let camera = Camera::new(options.size);
+    raster::render_view(&board, &camera, &options.theme).write_png(&options.output)
+}
+
+/// ## Errors
+/// If the board can't be loaded or the animation can't be written
+pub fn gif(options: &ExportOptions, gif_options: &GifOptions) -> Result<(), String> {
+    let board = initial_board(options)?;
+    animation::write_gif(board, options, gif_options)
+}
+
+/// Export the default view, or the given cells
+///
+/// ## Errors
+/// If the board can't be loaded or the file can't be written
+pub fn svg(options: &ExportOptions, svg_options: SvgOptions, region: Option<GridRect>) -> Result<(), String> {
+    let board = initial_board(options)?;
+    let camera = match region {
+        Some(region) => Camera::framing(region, options.cell_size as f64),
+        None => Camera::new(options.size)
+    };
+    let text = svg::render_svg(&board, &camera, &options.theme, svg_options);
+    fs::write(&options.output, text).map_err(|e| format!("{}: {}", options.output.display(), e))
+}
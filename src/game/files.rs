@@ -0,0 +1,67 @@
+//! Reading patterns from files, whatever their format
+
+use super::bitmap::{self, ImportOptions};
+use super::cells;
+use super::grid::GameGrid;
+use super::rle;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Extensions of the files that are read as patterns rather than images
+pub const PATTERN_EXTENSIONS: [&str; 2] = ["rle", "cells"];
+
+pub struct PatternFile {
+    pub pattern: GameGrid,
+    /// Given in the file, or else its stem
+    pub name: String,
+    pub rule: String,
+}
+
+/// `<dir>/<prefix>-<seconds since the epoch>.<extension>`, with a `-2`, `-3`…
+/// suffix when files were already saved in the same second
+pub fn timestamped_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut path = dir.join(format!("{}-{}.{}", prefix, timestamp, extension));
+    let mut count = 1;
+    while path.exists() {
+        count += 1;
+        path = dir.join(format!("{}-{}-{}.{}", prefix, timestamp, count, extension));
+    }
+    path
+}
+
+fn is_plaintext(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("cells"))
+}
+
+/// Read an RLE or a plaintext (`.cells`) pattern along with its metadata
+///
+/// ## Errors
+/// If the file can't be read or is not a valid pattern
+pub fn read_pattern_file(path: &Path) -> Result<PatternFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (pattern, name, rule) = if is_plaintext(path) {
+        (cells::decode(&text), cells::name(&text), None)
+    }
+    else {
+        (rle::decode(&text), rle::name(&text), rle::rule(&text))
+    };
+    Ok(PatternFile {
+        pattern: pattern.map_err(|e| format!("{}: {}", path.display(), e))?,
+        name: name.unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().into_owned()),
+        rule: rule.unwrap_or_else(|| String::from("B3/S23")),
+    })
+}
+
+/// Images are converted according to `image_options`, anything else is read as a pattern
+///
+/// ## Errors
+/// If the file can't be read or is not a valid pattern
+pub fn read_pattern(path: &Path, image_options: &ImportOptions) -> Result<GameGrid, String> {
+    if bitmap::is_image(path) {
+        return bitmap::import(path, image_options);
+    }
+    read_pattern_file(path).map(|file| file.pattern)
+}
@@ -1,13 +1,38 @@
+use super::positions::GridRect;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Shared by every grid so that two different contents never have the same revision
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone)]
 pub struct GameGrid {
     rows: usize,
     cols: usize,
     values: Vec<Vec<bool>>, // true = alive | false = dead
+    /// Changes with the cells, copies keep it
+    revision: u64,
+}
+
+impl PartialEq for GameGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
 }
 
 
 impl GameGrid {
     pub fn new(rows: usize, cols: usize) -> Self {
-        GameGrid { rows, cols, values: vec![vec![false; cols]; rows] }
+        GameGrid { rows, cols, values: vec![vec![false; cols]; rows], revision: NEXT_REVISION.fetch_add(1, Ordering::Relaxed) }
+    }
+
+    /// Different after any change of the cells, to know when something computed from them is outdated
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.revision = NEXT_REVISION.fetch_add(1, Ordering::Relaxed);
     }
 
     pub fn get_shape(&self) -> (usize, usize) {
@@ -27,15 +52,153 @@ impl GameGrid {
 
     /// ## Errors
     /// If the given position is outside the grid
-    pub fn switch_state_at(&mut self, row: usize, col: usize) -> Result<(), ()> {
+    pub fn set_state_at(&mut self, row: usize, col: usize, state: bool) -> Result<(), ()> {
         if self.is_in(row, col) {
-            let state = self.values[row][col];
-            self.values[row][col] = !state;
+            if self.values[row][col] != state {
+                self.values[row][col] = state;
+                self.touch();
+            }
             return Ok(());
         }
         Err(())
     }
 
+    /// Copy the alive cells of `pattern` with its top left corner at (`row`,`col`)
+    ///
+    /// Cells falling outside the grid are dropped
+    pub fn paste(&mut self, pattern: &GameGrid, row: usize, col: usize) {
+        for (dy, pattern_row) in pattern.values.iter().enumerate() {
+            for (dx, &alive) in pattern_row.iter().enumerate() {
+                if alive {
+                    let _ = self.set_state_at(row + dy, col + dx, true);
+                }
+            }
+        }
+    }
+
+    /// `(row, col)` of the alive cells, row by row
+    pub fn alive_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.values.iter().enumerate().flat_map(|(row, values)| {
+            values.iter().enumerate().filter(|(_, &alive)| alive).map(move |(col, _)| (row, col))
+        })
+    }
+
+    /// Copy of the cells inside the rectangle, those outside the grid are dead
+    pub fn extract(&self, rect: GridRect) -> GameGrid {
+        let mut pattern = GameGrid::new(rect.height, rect.width);
+        for dy in 0..rect.height {
+            for dx in 0..rect.width {
+                pattern.values[dy][dx] = self.get(rect.y + dy, rect.x + dx).unwrap_or(false);
+            }
+        }
+        pattern
+    }
+
+    /// Copy every cell of `pattern`, dead or alive, with its top left corner at (`row`,`col`)
+    pub fn overwrite(&mut self, pattern: &GameGrid, row: usize, col: usize) {
+        for (dy, pattern_row) in pattern.values.iter().enumerate() {
+            for (dx, &alive) in pattern_row.iter().enumerate() {
+                let _ = self.set_state_at(row + dy, col + dx, alive);
+            }
+        }
+    }
+
+    /// Rotated by 90° clockwise
+    pub fn rotated(&self) -> GameGrid {
+        let mut rotated = GameGrid::new(self.cols, self.rows);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                rotated.values[col][self.rows - 1 - row] = self.values[row][col];
+            }
+        }
+        rotated
+    }
+
+    /// Mirrored left to right
+    pub fn flipped_horizontally(&self) -> GameGrid {
+        let mut flipped = self.clone();
+        flipped.values.iter_mut().for_each(|row| row.reverse());
+        flipped.touch();
+        flipped
+    }
+
+    /// Mirrored top to bottom
+    pub fn flipped_vertically(&self) -> GameGrid {
+        let mut flipped = self.clone();
+        flipped.values.reverse();
+        flipped.touch();
+        flipped
+    }
+
+    /// Mirrored along the top left to bottom right diagonal
+    pub fn transposed(&self) -> GameGrid {
+        self.rotated().flipped_horizontally()
+    }
+
+    /// Switch every cell inside the rectangle
+    pub fn invert_rect(&mut self, rect: GridRect) {
+        for row in rect.y..(rect.y + rect.height).min(self.rows) {
+            for col in rect.x..(rect.x + rect.width).min(self.cols) {
+                self.values[row][col] = !self.values[row][col];
+            }
+        }
+        self.touch();
+    }
+
+    /// Kill every cell outside the rectangle
+    pub fn clear_outside(&mut self, rect: GridRect) {
+        let kept = self.extract(rect);
+        self.clear();
+        self.overwrite(&kept, rect.y, rect.x);
+    }
+
+    /// Kill every cell inside the rectangle
+    pub fn clear_rect(&mut self, rect: GridRect) {
+        for row in rect.y..(rect.y + rect.height).min(self.rows) {
+            for col in rect.x..(rect.x + rect.width).min(self.cols) {
+                self.values[row][col] = false;
+            }
+        }
+        self.touch();
+    }
+
+    /// Smallest rectangle containing every alive cell, `None` if there is none
+    pub fn bounding_box(&self) -> Option<GridRect> {
+        self.bounding_box_within(GridRect { x: 0, y: 0, width: self.cols, height: self.rows })
+    }
+
+    /// Smallest rectangle containing the alive cells of `rect`
+    pub fn bounding_box_within(&self, rect: GridRect) -> Option<GridRect> {
+        let bottom = (rect.y + rect.height).min(self.rows);
+        let right = (rect.x + rect.width).min(self.cols);
+        let mut bounds: Option<GridRect> = None;
+        for row in rect.y.min(bottom)..bottom {
+            let values = &self.values[row][rect.x.min(right)..right];
+            let first = values.iter().position(|&alive| alive);
+            let last = values.iter().rposition(|&alive| alive);
+            if let (Some(first), Some(last)) = (first, last) {
+                let row_bounds = GridRect { x: rect.x + first, y: row, width: last - first + 1, height: 1 };
+                bounds = Some(bounds.map_or(row_bounds, |bounds| bounds.union(row_bounds)));
+            }
+        }
+        bounds
+    }
+
+    /// Mean position `[x, y]` of the alive cells of `rect`, at the center of the cells
+    pub fn centroid(&self, rect: GridRect) -> Option<[f64; 2]> {
+        let (mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0.0);
+        for row in rect.y..(rect.y + rect.height).min(self.rows) {
+            for col in rect.x..(rect.x + rect.width).min(self.cols) {
+                if self.values[row][col] {
+                    sum_x += col as f64 + 0.5;
+                    sum_y += row as f64 + 0.5;
+                    count += 1.0;
+                }
+            }
+        }
+        (count > 0.0).then(|| [sum_x / count, sum_y / count])
+    }
+
     /// Clear the board to an empty board
     pub fn clear(&mut self) {
         self.values.iter_mut().for_each(|row| {
@@ -43,6 +206,7 @@ impl GameGrid {
                 *cell = false;
             });
         });
+        self.touch();
     }
 
     /// ## Panics
@@ -83,5 +247,6 @@ impl GameGrid {
         }
         
         self.values = new_values;
+        self.touch();
     }
 }
\ No newline at end of file
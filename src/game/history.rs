@@ -0,0 +1,205 @@
+//! Undo and redo of edits and runs
+//!
+//! An edit is stored as the cells it switched, a run as RLE snapshots of the
+//! board before and after it, with their generations. The oldest changes are forgotten when the
+//! history is larger than its memory budget.
+
+use super::grid::GameGrid;
+use super::rle;
+
+use std::collections::VecDeque;
+use std::mem::size_of;
+
+enum Change {
+    /// `(row, col)` of the switched cells, undone by switching them back
+    Edit(Vec<(usize, usize)>),
+    /// Generation and RLE snapshot of the board
+    Run { before: (u64, String), after: (u64, String) },
+}
+
+impl Change {
+    /// Approximate memory used, in bytes
+    fn size(&self) -> usize {
+        match self {
+            Change::Edit(cells) => cells.len() * size_of::<(usize, usize)>(),
+            Change::Run { before, after } => before.1.len() + after.1.len()
+        }
+    }
+}
+
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    /// In bytes
+    budget: usize,
+    size: usize,
+    /// Generation and board when the current run started
+    run_start: Option<(u64, String)>,
+}
+
+impl History {
+    pub fn new(budget: usize) -> Self {
+        History { undo: VecDeque::new(), redo: Vec::new(), budget, size: 0, run_start: None }
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.forget_oldest();
+    }
+
+    /// Record the cells that differ, return whether there are some
+    pub fn record_edit(&mut self, before: &GameGrid, after: &GameGrid) -> bool {
+        let (rows, cols) = after.get_shape();
+        let cells: Vec<(usize, usize)> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .filter(|&(row, col)| before.get(row, col) != after.get(row, col))
+            .collect();
+        if cells.is_empty() {
+            return false;
+        }
+        self.push(Change::Edit(cells));
+        true
+    }
+
+    pub fn begin_run(&mut self, board: &GameGrid, generation: u64) {
+        self.run_start = Some((generation, rle::encode(board)));
+    }
+
+    pub fn end_run(&mut self, board: &GameGrid, generation: u64) {
+        if let Some(before) = self.run_start.take() {
+            let after = (generation, rle::encode(board));
+            if after != before {
+                self.push(Change::Run { before, after });
+            }
+        }
+    }
+
+    /// Return the generation of the restored board when a run is undone,
+    /// `None` when an edit is, the generation staying the same
+    ///
+    /// ## Errors
+    /// If there is nothing to undo
+    pub fn undo(&mut self, board: &mut GameGrid) -> Result<Option<u64>, ()> {
+        let change = self.undo.pop_back().ok_or(())?;
+        let generation = match &change {
+            Change::Edit(cells) => {
+                switch_cells(board, cells);
+                None
+            },
+            Change::Run { before, .. } => Some(restore(board, before))
+        };
+        self.redo.push(change);
+        Ok(generation)
+    }
+
+    /// Like `undo`
+    ///
+    /// ## Errors
+    /// If there is nothing to redo
+    pub fn redo(&mut self, board: &mut GameGrid) -> Result<Option<u64>, ()> {
+        let change = self.redo.pop().ok_or(())?;
+        let generation = match &change {
+            Change::Edit(cells) => {
+                switch_cells(board, cells);
+                None
+            },
+            Change::Run { after, .. } => Some(restore(board, after))
+        };
+        self.undo.push_back(change);
+        Ok(generation)
+    }
+
+    /// A new change makes the undone ones unreachable
+    fn push(&mut self, change: Change) {
+        self.size -= self.redo.drain(..).map(|change| change.size()).sum::<usize>();
+        self.size += change.size();
+        self.undo.push_back(change);
+        self.forget_oldest();
+    }
+
+    fn forget_oldest(&mut self) {
+        while self.size > self.budget {
+            let Some(change) = self.undo.pop_front() else {
+                break;
+            };
+            self.size -= change.size();
+        }
+    }
+}
+
+fn switch_cells(board: &mut GameGrid, cells: &[(usize, usize)]) {
+    for &(row, col) in cells {
+        let alive = board.get(row, col).unwrap_or(false);
+        let _ = board.set_state_at(row, col, !alive);
+    }
+}
+
+/// Return the generation of the snapshot
+fn restore(board: &mut GameGrid, (generation, snapshot): &(u64, String)) -> u64 {
+    *board = rle::decode(snapshot).expect("Snapshots are valid RLE");
+    *generation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_with(cells: &[(usize, usize)]) -> GameGrid {
+        let mut board = GameGrid::new(8, 8);
+        for &(row, col) in cells {
+            board.set_state_at(row, col, true).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let mut history = History::new(usize::MAX);
+        let empty = board_with(&[]);
+        let mut board = board_with(&[(1, 1), (2, 3)]);
+        assert!(history.record_edit(&empty, &board));
+        assert!(!history.record_edit(&board, &board));
+
+        assert_eq!(history.undo(&mut board), Ok(None));
+        assert!(board == empty);
+        assert_eq!(history.undo(&mut board), Err(()));
+        assert_eq!(history.redo(&mut board), Ok(None));
+        assert!(board == board_with(&[(1, 1), (2, 3)]));
+        assert_eq!(history.redo(&mut board), Err(()));
+    }
+
+    #[test]
+    fn runs_restore_their_generation() {
+        let mut history = History::new(usize::MAX);
+        let blinker = board_with(&[(2, 1), (2, 2), (2, 3)]);
+        let mut board = blinker.clone();
+        history.begin_run(&board, 4);
+        board.next_generation();
+        history.end_run(&board, 5);
+        let after = board.clone();
+
+        assert_eq!(history.undo(&mut board), Ok(Some(4)));
+        assert!(board == blinker);
+        assert_eq!(history.redo(&mut board), Ok(Some(5)));
+        assert!(board == after);
+    }
+
+    #[test]
+    fn new_change_drops_redo() {
+        let mut history = History::new(usize::MAX);
+        let mut board = board_with(&[(0, 0)]);
+        history.record_edit(&board_with(&[]), &board);
+        history.undo(&mut board).unwrap();
+        history.record_edit(&board, &board_with(&[(5, 5)]));
+        assert_eq!(history.redo(&mut board), Err(()));
+    }
+
+    #[test]
+    fn budget_forgets_oldest() {
+        let mut history = History::new(usize::MAX);
+        let mut board = board_with(&[(0, 0)]);
+        history.record_edit(&board_with(&[]), &board);
+        history.set_budget(0);
+        assert_eq!(history.undo(&mut board), Err(()));
+    }
+}
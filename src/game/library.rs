@@ -0,0 +1,48 @@
+//! Built-in catalog of well-known patterns, embedded as RLE
+
+use super::grid::GameGrid;
+use super::rle;
+
+pub struct LibraryEntry {
+    pub name: &'static str,
+    rle: &'static str,
+}
+
+impl LibraryEntry {
+    pub fn pattern(&self) -> GameGrid {
+        rle::decode(self.rle).expect("Built-in patterns are valid RLE")
+    }
+}
+
+pub const LIBRARY: [LibraryEntry; 18] = [
+    LibraryEntry { name: "Glider", rle: "x = 3, y = 3\nbo$2bo$3o!" },
+    LibraryEntry { name: "Lightweight spaceship (LWSS)", rle: "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!" },
+    LibraryEntry { name: "Middleweight spaceship (MWSS)", rle: "x = 6, y = 5\n3bo2b$bo3bo$o5b$o4bo$5o!" },
+    LibraryEntry { name: "Heavyweight spaceship (HWSS)", rle: "x = 7, y = 5\n3b2o2b$bo4bo$o6b$o5bo$6o!" },
+    LibraryEntry { name: "Blinker", rle: "x = 3, y = 1\n3o!" },
+    LibraryEntry { name: "Toad", rle: "x = 4, y = 2\nb3o$3o!" },
+    LibraryEntry { name: "Beacon", rle: "x = 4, y = 4\n2o2b$o3b$3bo$2b2o!" },
+    LibraryEntry {
+        name: "Pulsar",
+        rle: "x = 13, y = 13\n2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$\
+              o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!"
+    },
+    LibraryEntry { name: "Pentadecathlon", rle: "x = 10, y = 3\n2bo4bo2b$2ob4ob2o$2bo4bo!" },
+    LibraryEntry { name: "Block", rle: "x = 2, y = 2\n2o$2o!" },
+    LibraryEntry { name: "Beehive", rle: "x = 4, y = 3\nb2ob$o2bo$b2o!" },
+    LibraryEntry { name: "Loaf", rle: "x = 4, y = 4\nb2ob$o2bo$bobo$2bo!" },
+    LibraryEntry {
+        name: "Gosper glider gun",
+        rle: "x = 36, y = 9\n24bo11b$22bobo11b$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o14b$\
+              2o8bo3bob2o4bobo11b$10bo5bo7bo11b$11bo3bo20b$12b2o!"
+    },
+    LibraryEntry {
+        name: "Simkin glider gun",
+        rle: "x = 33, y = 21\n2o5b2o$2o5b2o2$4b2o$4b2o5$22b2ob2o$21bo5bo$21bo6bo2b2o$\
+              21b3o3bo3b2o$26bo4$20b2o$20bo$21b3o$23bo!"
+    },
+    LibraryEntry { name: "R-pentomino", rle: "x = 3, y = 3\nb2o$2ob$bo!" },
+    LibraryEntry { name: "Acorn", rle: "x = 7, y = 3\nbo5b$3bo3b$2o2b3o!" },
+    LibraryEntry { name: "Diehard", rle: "x = 8, y = 3\n6bob$2o6b$bo3b3o!" },
+    LibraryEntry { name: "Infinite growth", rle: "x = 5, y = 5\n3obo$o4b$3b2o$b2obo$obobo!" },
+];
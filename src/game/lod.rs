@@ -0,0 +1,64 @@
+//! Level of detail when cells are smaller than a pixel
+//!
+//! Cells are aggregated in square blocks of a power of two side, each block
+//! covering at least a pixel and drawn with an opacity growing with its density.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::theme::Theme;
+use crate::consts::LOD_CELL_PIXELS;
+
+use graphics::{rectangle, Context};
+use opengl_graphics::GlGraphics;
+
+/// Side in cells of the blocks to draw, 1 when cells are large enough to be drawn one by one
+pub fn block_size(cell_length: f64) -> usize {
+    // Nothing is drawn in a minimized window
+    if cell_length <= 0.0 || !cell_length.is_finite() {
+        return 1;
+    }
+    let mut block = 1;
+    while (block as f64) * cell_length < LOD_CELL_PIXELS {
+        block *= 2;
+    }
+    block
+}
+
+/// Screen rectangle `[x, y, width, height]` and opacity of the visible blocks of
+/// `block` cells that have alive cells, also used by the exports of the view
+///
+/// Blocks are aligned on multiples of their size so they do not shimmer while panning.
+pub fn visible_blocks<'a>(board: &'a GameGrid, camera: &'a Camera, block: usize) -> impl Iterator<Item = ([f64; 4], f32)> + 'a {
+    let visible = camera.visible_cells();
+    let side = block as f64 * camera.cell_lenght();
+    (visible.y / block..(visible.y + visible.height).div_ceil(block)).flat_map(move |block_row| {
+        (visible.x / block..(visible.x + visible.width).div_ceil(block)).filter_map(move |block_col| {
+            let alpha = block_alpha(board, block_row * block, block_col * block, block)?;
+            let [x, y] = camera.project([(block_col * block) as f64, (block_row * block) as f64]);
+            Some((rectangle::square(x, y, side), alpha))
+        })
+    })
+}
+
+/// Draws the visible part of the board by blocks of `block` cells
+pub fn render(board: &GameGrid, camera: &Camera, block: usize, theme: &Theme, context: &Context, gl: &mut GlGraphics) {
+    for (rect, alpha) in visible_blocks(board, camera, block) {
+        rectangle([theme.cell[0], theme.cell[1], theme.cell[2], alpha], rect, context.transform, gl);
+    }
+}
+
+/// Opacity of the block of `block` cells by `block` cells starting at `(row, col)`,
+/// growing with its density of alive cells, `None` if it has none
+pub fn block_alpha(board: &GameGrid, row: usize, col: usize, block: usize) -> Option<f32> {
+    let (rows, cols) = board.get_shape();
+    let mut alive = 0;
+    for row in row..(row + block).min(rows) {
+        for col in col..(col + block).min(cols) {
+            if board.get(row, col).unwrap() {
+                alive += 1;
+            }
+        }
+    }
+    // Even a single cell must be visible
+    (alive > 0).then(|| (alive as f32 / (block * block) as f32 * 4.0).clamp(0.3, 1.0))
+}
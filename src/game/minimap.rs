@@ -0,0 +1,64 @@
+//! Overview of the whole world in a corner of the window
+//!
+//! Each block of cells is drawn with an opacity growing with its density of
+//! alive cells, the part seen by the camera is outlined.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::lod;
+use super::positions::{ScreenPosition, WorldPosition};
+use super::theme::Theme;
+use crate::consts::{MINIMAP_BLOCKS, MINIMAP_MARGIN, MINIMAP_SIZE, SCRUBBER_HEIGHT, WORLD_HEIGHT, WORLD_WIDTH};
+
+use graphics::{rectangle, Context, Rectangle};
+use opengl_graphics::GlGraphics;
+
+/// `[x, y, width, height]` of the minimap, in the bottom right corner above the timeline
+fn area(draw_size: [f64; 2]) -> [f64; 4] {
+    let width = MINIMAP_SIZE.min(draw_size[0] / 3.0).min((draw_size[1] - SCRUBBER_HEIGHT) / 3.0 * WORLD_WIDTH / WORLD_HEIGHT);
+    let height = width * WORLD_HEIGHT / WORLD_WIDTH;
+    [draw_size[0] - width - MINIMAP_MARGIN, draw_size[1] - SCRUBBER_HEIGHT - height - MINIMAP_MARGIN, width, height]
+}
+
+/// World position shown under the cursor, if it is on the minimap
+pub fn world_at(position: ScreenPosition, draw_size: [f64; 2]) -> Option<WorldPosition> {
+    let [x, y, width, height] = area(draw_size);
+    let inside = (x..x + width).contains(&position[0]) && (y..y + height).contains(&position[1]);
+    inside.then(|| [(position[0] - x) / width * WORLD_WIDTH, (position[1] - y) / height * WORLD_HEIGHT])
+}
+
+pub fn render(board: &GameGrid, camera: &Camera, theme: &Theme, context: &Context, gl: &mut GlGraphics) {
+    let [x, y, width, height] = area(camera.draw_size());
+    let [r, g, b, _] = theme.background;
+    rectangle([r, g, b, 0.85], [x, y, width, height], context.transform, gl);
+    Rectangle::new_border([theme.cell[0], theme.cell[1], theme.cell[2], 0.3], 1.0)
+        .draw([x, y, width, height], &context.draw_state, context.transform, gl);
+
+    let (rows, cols) = board.get_shape();
+    let block = rows.max(cols).div_ceil(MINIMAP_BLOCKS).max(1);
+    let (block_width, block_height) = (width / cols as f64 * block as f64, height / rows as f64 * block as f64);
+    for block_row in 0..rows.div_ceil(block) {
+        for block_col in 0..cols.div_ceil(block) {
+            if let Some(alpha) = lod::block_alpha(board, block_row * block, block_col * block, block) {
+                rectangle(
+                    [theme.cell[0], theme.cell[1], theme.cell[2], alpha],
+                    [x + block_col as f64 * block_width, y + block_row as f64 * block_height, block_width, block_height],
+                    context.transform,
+                    gl
+                );
+            }
+        }
+    }
+
+    // Clipped to the world when zoomed out beyond it
+    let [view_x, view_y, view_width, view_height] = camera.view();
+    let (left, top) = (view_x.max(0.0), view_y.max(0.0));
+    let (right, bottom) = ((view_x + view_width).min(WORLD_WIDTH), (view_y + view_height).min(WORLD_HEIGHT));
+    let scale = [width / WORLD_WIDTH, height / WORLD_HEIGHT];
+    Rectangle::new_border(theme.cell, 1.0).draw(
+        [x + left * scale[0], y + top * scale[1], (right - left) * scale[0], (bottom - top) * scale[1]],
+        &context.draw_state,
+        context.transform,
+        gl
+    );
+}
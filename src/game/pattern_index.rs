@@ -0,0 +1,178 @@
+//! Index of a directory of pattern files
+//!
+//! A worker thread rescans the directory every `PATTERN_SCAN_INTERVAL` seconds,
+//! only the files whose modification time changed are read again. The index is
+//! cached in the state directory so that it is available right away at launch.
+
+use super::autosave::state_dir;
+use super::files::{self, PATTERN_EXTENSIONS};
+use super::grid::GameGrid;
+use super::rle;
+use crate::consts::{PATTERN_SCAN_INTERVAL, THUMBNAIL_CELLS};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+const CACHE_FILE: &str = "pattern-index.tsv";
+
+#[derive(Clone)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    /// Seconds since the epoch
+    modified: u64,
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub rule: String,
+    /// At most `THUMBNAIL_CELLS` wide and high
+    pub thumbnail: GameGrid,
+}
+
+pub struct PatternIndex {
+    dir: PathBuf,
+    entries: Vec<IndexEntry>,
+    receiver: Receiver<Vec<IndexEntry>>,
+}
+
+impl PatternIndex {
+    /// Start indexing the directory in the background
+    pub fn watch(dir: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker_dir = dir.clone();
+        thread::spawn(move || worker(&worker_dir, &sender));
+        PatternIndex { dir, entries: Vec::new(), receiver }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Take the latest index published by the worker
+    pub fn poll(&mut self) {
+        if let Some(entries) = self.receiver.try_iter().last() {
+            self.entries = entries;
+        }
+    }
+
+    /// Sorted by name
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+}
+
+/// Shrink a pattern, a cell of the thumbnail is alive if any cell of its block is
+fn thumbnail(pattern: &GameGrid) -> GameGrid {
+    let (rows, cols) = pattern.get_shape();
+    let factor = rows.max(cols).div_ceil(THUMBNAIL_CELLS).max(1);
+    let mut thumbnail = GameGrid::new(rows.div_ceil(factor), cols.div_ceil(factor));
+    for row in 0..rows {
+        for col in 0..cols {
+            if pattern.get(row, col).unwrap() {
+                let _ = thumbnail.set_state_at(row / factor, col / factor, true);
+            }
+        }
+    }
+    thumbnail
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let time = fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+    time.duration_since(UNIX_EPOCH).ok().map(|duration| duration.as_secs())
+}
+
+fn read_entry(path: &Path, modified: u64) -> Option<IndexEntry> {
+    let file = files::read_pattern_file(path).ok()?;
+    let (height, width) = file.pattern.get_shape();
+    Some(IndexEntry {
+        path: path.to_path_buf(),
+        modified,
+        name: file.name,
+        width,
+        height,
+        rule: file.rule,
+        thumbnail: thumbnail(&file.pattern),
+    })
+}
+
+/// Every pattern file of the directory, reusing the entries that did not change
+fn scan(dir: &Path, previous: &[IndexEntry]) -> Vec<IndexEntry> {
+    let known: HashMap<&Path, &IndexEntry> = previous.iter().map(|entry| (entry.path.as_path(), entry)).collect();
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<IndexEntry> = read_dir
+        .filter_map(|dir_entry| dir_entry.ok().map(|dir_entry| dir_entry.path()))
+        .filter(|path| path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| PATTERN_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())))
+        .filter_map(|path| {
+            let modified = modified(&path)?;
+            match known.get(path.as_path()) {
+                Some(entry) if entry.modified == modified => Some((*entry).clone()),
+                _ => read_entry(&path, modified)
+            }
+        })
+        .collect();
+    entries.sort_by_cached_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+fn same_files(a: &[IndexEntry], b: &[IndexEntry]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.path == b.path && a.modified == b.modified)
+}
+
+fn worker(dir: &Path, sender: &Sender<Vec<IndexEntry>>) {
+    let cache_path = state_dir().join(CACHE_FILE);
+    let mut entries: Vec<IndexEntry> = load_cache(&cache_path).into_iter()
+        .filter(|entry| entry.path.parent() == Some(dir))
+        .collect();
+    if sender.send(entries.clone()).is_err() {
+        return;
+    }
+
+    loop {
+        let scanned = scan(dir, &entries);
+        if !same_files(&scanned, &entries) {
+            entries = scanned;
+            save_cache(&cache_path, &entries);
+            if sender.send(entries.clone()).is_err() {
+                return; // The game is gone
+            }
+        }
+        thread::sleep(Duration::from_secs_f64(PATTERN_SCAN_INTERVAL));
+    }
+}
+
+/// One entry per line, tab separated, the thumbnail being RLE with `|` for new lines
+fn save_cache(path: &Path, entries: &[IndexEntry]) {
+    let text: String = entries.iter().map(|entry| format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+        entry.path.display(), entry.modified, entry.name.replace('\t', " "), entry.width, entry.height,
+        entry.rule, rle::encode(&entry.thumbnail).trim_end().replace('\n', "|"))).collect();
+    let _ = fs::write(path, text);
+}
+
+fn load_cache(path: &Path) -> Vec<IndexEntry> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [path, modified, name, width, height, rule, thumbnail] = fields[..] else {
+            return None;
+        };
+        Some(IndexEntry {
+            path: PathBuf::from(path),
+            modified: modified.parse().ok()?,
+            name: name.to_string(),
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+            rule: rule.to_string(),
+            thumbnail: rle::decode(&thumbnail.replace('|', "\n")).ok()?,
+        })
+    }).collect()
+}
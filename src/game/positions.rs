@@ -20,6 +20,29 @@ pub type ScreenPosition = [f64; 2];
 #[derive(PartialEq, Eq, Hash)]
 pub struct GridPosition(pub usize, pub usize);
 
+/// A rectangle of cells, `x` and `y` being its top left cell
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GridRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl GridRect {
+    /// Smallest rectangle containing both
+    pub fn union(self, other: GridRect) -> GridRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        GridRect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
 pub fn screen_to_world(position: ScreenPosition, camera: &Camera) -> WorldPosition {
     camera.screen_to_world(position)
 }
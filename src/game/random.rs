@@ -0,0 +1,22 @@
+//! Small pseudo random generator, good enough to scatter cells
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// xorshift64*
+pub struct Random(u64);
+
+impl Random {
+    /// Seeded from the clock
+    pub fn new() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        Random(nanos | 1)
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,151 @@
+//! Software renderer
+//!
+//! Draws the same view as `Game::render` into an RGBA buffer, without any
+//! OpenGL context, so that it can be used headless.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::lod;
+use super::positions::{GridPosition, GridRect};
+use super::theme::{to_rgba8, Theme};
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// RGBA8 pixel buffer, rows from top to bottom
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, background: [f32; 4]) -> Self {
+        Canvas { width, height, pixels: to_rgba8(background).repeat(width * height) }
+    }
+
+    /// Index of each pixel in `[background, cell]`, any pixel that is not the background is a cell
+    pub fn to_indexed(&self, theme: &Theme) -> Vec<u8> {
+        let background = to_rgba8(theme.background);
+        self.pixels.chunks_exact(4).map(|pixel| u8::from(pixel != background)).collect()
+    }
+
+    /// Fill every pixel whose center is inside the rectangle, clipped to the canvas
+    ///
+    /// The color is blended over the canvas according to its alpha
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        let x_start = (x - 0.5).ceil().max(0.0) as usize;
+        let y_start = (y - 0.5).ceil().max(0.0) as usize;
+        let x_end = ((x + width - 0.5).ceil().max(0.0) as usize).min(self.width);
+        let y_end = ((y + height - 0.5).ceil().max(0.0) as usize).min(self.height);
+
+        let source = to_rgba8(color);
+        let alpha = color[3].clamp(0.0, 1.0);
+        for row in y_start..y_end {
+            for col in x_start..x_end {
+                let index = (row * self.width + col) * 4;
+                let pixel = &mut self.pixels[index..index + 4];
+                for channel in 0..3 {
+                    let blended = source[channel] as f32 * alpha + pixel[channel] as f32 * (1.0 - alpha);
+                    pixel[channel] = blended.round() as u8;
+                }
+                pixel[3] = pixel[3].max(source[3]);
+            }
+        }
+    }
+
+    /// ## Errors
+    /// If the file can't be created or written
+    pub fn write_png(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Render what the camera sees, the canvas has the shape of the camera's screen
+pub fn render_view(board: &GameGrid, camera: &Camera, theme: &Theme) -> Canvas {
+    let [width, height] = camera.draw_size();
+    let mut canvas = Canvas::new(width.round() as usize, height.round() as usize, theme.background);
+    let cell_length = camera.cell_lenght();
+
+    // Cells smaller than a pixel are drawn by blocks, as in the window
+    let block = lod::block_size(cell_length);
+    if block > 1 {
+        for ([x, y, width, height], alpha) in lod::visible_blocks(board, camera, block) {
+            canvas.fill_rect(x, y, width, height, [theme.cell[0], theme.cell[1], theme.cell[2], alpha]);
+        }
+        return canvas;
+    }
+
+    let visible = camera.visible_cells();
+    for y in visible.y..visible.y + visible.height {
+        for x in visible.x..visible.x + visible.width {
+            if board.get(y, x).unwrap() {
+                if let Some(screen_pos) = GridPosition(x, y).to_screen_position(camera) {
+                    canvas.fill_rect(screen_pos[0], screen_pos[1], cell_length, cell_length, theme.cell);
+                }
+            }
+        }
+    }
+
+    canvas
+}
+
+/// Render a rectangle of cells, each cell being `cell_size` pixels wide
+pub fn render_region(board: &GameGrid, region: GridRect, cell_size: usize, theme: &Theme) -> Canvas {
+    let mut canvas = Canvas::new(region.width * cell_size, region.height * cell_size, theme.background);
+    let size = cell_size as f64;
+    for dy in 0..region.height {
+        for dx in 0..region.width {
+            if board.get(region.y + dy, region.x + dx).unwrap_or(false) {
+                canvas.fill_rect(dx as f64 * size, dy as f64 * size, size, size, theme.cell);
+            }
+        }
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit_pixels(canvas: &Canvas, theme: &Theme) -> usize {
+        let background = to_rgba8(theme.background);
+        canvas.pixels.chunks_exact(4).filter(|pixel| *pixel != background).count()
+    }
+
+    #[test]
+    fn cells_smaller_than_a_pixel_stay_visible() {
+        let theme = Theme::default();
+        let mut camera = Camera::new([125.0, 125.0]);
+        for _ in 0..20 {
+            camera.wheel_zoom(false, [62.5, 62.5]);
+            camera.update_zoom(10.0);
+        }
+        assert!(camera.cell_lenght() < 1.0);
+        let mut board = GameGrid::new(500, 500);
+        for row in 200..258 {
+            for col in 200..258 {
+                board.set_state_at(row, col, true).unwrap();
+            }
+        }
+        let side = (58.0 * camera.cell_lenght()).floor() as usize;
+        assert!(lit_pixels(&render_view(&board, &camera, &theme), &theme) >= side * side);
+    }
+
+    #[test]
+    fn region_has_cell_size_pixels_per_cell() {
+        let theme = Theme::default();
+        let mut board = GameGrid::new(4, 4);
+        board.set_state_at(1, 2, true).unwrap();
+        let canvas = render_region(&board, GridRect { x: 0, y: 0, width: 4, height: 4 }, 3, &theme);
+        assert_eq!((canvas.width, canvas.height), (12, 12));
+        assert_eq!(lit_pixels(&canvas, &theme), 9);
+    }
+}
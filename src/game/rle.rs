@@ -0,0 +1,206 @@
+//! Run Length Encoded patterns
+//!
+//! The de facto standard format for Life patterns, e.g. :
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+
+use super::grid::GameGrid;
+use crate::consts::MAX_PATTERN_CELLS;
+
+/// Lines of the encoded body are wrapped at this length
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Encode the whole grid, trailing dead cells of each row are omitted
+pub fn encode(grid: &GameGrid) -> String {
+    let (rows, cols) = grid.get_shape();
+    let mut tokens = Vec::new();
+    let mut pending_rows = 0;
+
+    for row in 0..rows {
+        let mut run: Option<(bool, usize)> = None;
+        let mut row_tokens = Vec::new();
+        for col in 0..cols {
+            let state = grid.get(row, col).unwrap();
+            run = match run {
+                Some((run_state, count)) if run_state == state => Some((state, count + 1)),
+                Some((run_state, count)) => {
+                    row_tokens.push(run_token(count, if run_state {'o'} else {'b'}));
+                    Some((state, 1))
+                },
+                None => Some((state, 1))
+            };
+        }
+        if let Some((true, count)) = run { // Trailing dead cells are implied
+            row_tokens.push(run_token(count, 'o'));
+        }
+
+        if row_tokens.is_empty() {
+            pending_rows += 1;
+        }
+        else {
+            if pending_rows > 0 { // Also keeps the offset of leading empty rows
+                tokens.push(run_token(pending_rows, '$'));
+            }
+            tokens.append(&mut row_tokens);
+            pending_rows = 1;
+        }
+    }
+    tokens.push(String::from("!"));
+
+    let mut text = format!("x = {}, y = {}, rule = B3/S23\n", cols, rows);
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        text.push_str(&token);
+    }
+    text.push('\n');
+    text
+}
+
+fn run_token(count: usize, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    }
+    else {
+        format!("{}{}", count, tag)
+    }
+}
+
+/// Decode a pattern, the grid has the shape given in the header
+///
+/// ## Errors
+/// If the header is missing or larger than `MAX_PATTERN_CELLS`, or the body is malformed
+pub fn decode(text: &str) -> Result<GameGrid, String> {
+    let mut lines = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let header = lines.next().ok_or("Missing RLE header")?;
+    let (cols, rows) = parse_header(header)?;
+    if rows.checked_mul(cols).is_none_or(|cells| cells > MAX_PATTERN_CELLS) {
+        return Err(format!("The {}x{} pattern is larger than {} cells", cols, rows, MAX_PATTERN_CELLS));
+    }
+    let mut grid = GameGrid::new(rows, cols);
+
+    let (mut row, mut col) = (0usize, 0usize);
+    let mut count = String::new();
+    'body: for line in lines {
+        for c in line.chars() {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+            let run = if count.is_empty() {1} else {
+                count.parse::<usize>().map_err(|e| e.to_string())?
+            };
+            count.clear();
+            match c {
+                'b' | '.' => col = col.checked_add(run).ok_or("Run too long")?,
+                '$' => {
+                    row = row.checked_add(run).ok_or("Run too long")?;
+                    col = 0;
+                },
+                '!' => break 'body,
+                c if c.is_whitespace() => {},
+                _ => { // Every other tag is an alive state
+                    for _ in 0..run {
+                        grid.set_state_at(row, col, true)
+                            .map_err(|_| format!("Cell ({},{}) is outside the {}x{} pattern", col, row, cols, rows))?;
+                        col += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+/// Name given by a `#N` line
+pub fn name(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("#N"))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Rule given in the header, like `B3/S23`
+pub fn rule(text: &str) -> Option<String> {
+    let header = text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    header.split(',').find_map(|field| {
+        let (key, value) = field.split_once('=')?;
+        (key.trim() == "rule").then(|| value.trim().to_string())
+    })
+}
+
+/// Return `(width, height)`
+fn parse_header(header: &str) -> Result<(usize, usize), String> {
+    let mut width = None;
+    let mut height = None;
+    for field in header.split(',') {
+        let mut parts = field.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        match key {
+            "x" => width = value.parse().ok(),
+            "y" => height = value.parse().ok(),
+            _ => {}
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!("Invalid RLE header: {:?}", header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> GameGrid {
+        let mut grid = GameGrid::new(8, 6);
+        for (row, col) in [(3, 1), (4, 2), (5, 0), (5, 1), (5, 2)] {
+            grid.set_state_at(row, col, true).unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn roundtrip_keeps_shape_and_cells() {
+        let text = encode(&glider());
+        assert!(text.starts_with("x = 6, y = 8"));
+        let decoded = decode(&text).unwrap();
+        assert_eq!(decoded.get_shape(), (8, 6));
+        assert!(decoded == glider());
+    }
+
+    #[test]
+    fn reads_name_and_rule() {
+        let text = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+        assert_eq!(name(text).as_deref(), Some("Glider"));
+        assert_eq!(rule(text).as_deref(), Some("B3/S23"));
+        assert_eq!(decode(text).unwrap().alive_cells().count(), 5);
+    }
+
+    #[test]
+    fn rejects_oversize_header() {
+        assert!(decode("x = 5000000, y = 5000000\n!").is_err());
+        assert!(decode(&format!("x = {}, y = 2\n!", usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn rejects_runs_past_the_pattern() {
+        assert!(decode("x = 3, y = 3\n4o!").is_err());
+        assert!(decode(&format!("x = 3, y = 3\n{}b{}bo!", usize::MAX, usize::MAX)).is_err());
+        assert!(decode(&format!("x = 3, y = 3\n{}${}$o!", usize::MAX, usize::MAX)).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Alive cells drawn as horizontal runs, only where the camera sees them
+//!
+//! Consecutive alive cells of a row are a single rectangle, so the drawing
+//! time grows with the visible area and not with the size of the world.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+
+use graphics::{rectangle, Context};
+use opengl_graphics::GlGraphics;
+
+/// Draw the alive cells of `cells`, its top left cell being at `origin` `[x, y]` of the world
+pub fn render(cells: &GameGrid, origin: [usize; 2], camera: &Camera, color: [f32; 4], context: &Context, gl: &mut GlGraphics) {
+    let (rows, cols) = cells.get_shape();
+    let visible = camera.visible_cells();
+    let (top, bottom) = (visible.y.saturating_sub(origin[1]), (visible.y + visible.height).saturating_sub(origin[1]).min(rows));
+    let (left, right) = (visible.x.saturating_sub(origin[0]), (visible.x + visible.width).saturating_sub(origin[0]).min(cols));
+    let cell_length = camera.cell_lenght();
+    for row in top..bottom {
+        let mut col = left;
+        while col < right {
+            if !cells.get(row, col).unwrap() {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < right && cells.get(row, col).unwrap() {
+                col += 1;
+            }
+            let [x, y] = camera.project([(origin[0] + start) as f64, (origin[1] + row) as f64]);
+            rectangle(color, [x, y, (col - start) as f64 * cell_length, cell_length], context.transform, gl);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Fixed timestep of the simulation, independent of the frame rate
+//!
+//! Steps are owed at the target rate and as many as needed are run at once,
+//! within `FRAME_WORK_BUDGET` so that commands are still handled. The steps
+//! that could not be caught up are dropped instead of piling up.
+
+use crate::consts::FRAME_WORK_BUDGET;
+
+use std::time::Instant;
+
+#[derive(Default)]
+pub struct Scheduler {
+    /// Steps due, the fractional part being carried to the next run
+    owed: f64,
+}
+
+impl Scheduler {
+    /// Run the steps due after `dt` seconds at `rate` steps per second,
+    /// an infinite rate meaning as fast as possible. Return how many were run.
+    ///
+    /// A step returning `false` was interrupted, it is neither counted nor
+    /// paid and the next call calls `step` for it again, to resume it.
+    pub fn run(&mut self, dt: f64, rate: f64, mut step: impl FnMut() -> bool) -> u64 {
+        let start = Instant::now();
+        self.owed = if rate.is_infinite() {f64::INFINITY} else {self.owed + dt * rate};
+        let mut count = 0;
+        while self.owed >= 1.0 && start.elapsed().as_secs_f64() < FRAME_WORK_BUDGET {
+            if !step() {
+                return count;
+            }
+            self.owed -= 1.0;
+            count += 1;
+        }
+        if self.owed >= 1.0 { // Behind schedule
+            self.owed = 0.0;
+        }
+        count
+    }
+
+    /// Forget the time elapsed so far, when the game starts playing
+    pub fn reset(&mut self) {
+        self.owed = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_fractional_steps() {
+        let mut scheduler = Scheduler::default();
+        assert_eq!(scheduler.run(0.25, 10.0, || true), 2);
+        assert_eq!(scheduler.run(0.05, 10.0, || true), 1);
+        assert_eq!(scheduler.run(0.0, 10.0, || true), 0);
+    }
+
+    #[test]
+    fn interrupted_step_is_still_due() {
+        let mut scheduler = Scheduler::default();
+        let mut calls = 0;
+        assert_eq!(scheduler.run(0.1, 10.0, || {
+            calls += 1;
+            false
+        }), 0);
+        // No time passed, the interrupted step is run by the next call
+        assert_eq!(scheduler.run(0.0, 10.0, || {
+            calls += 1;
+            true
+        }), 1);
+        assert_eq!(calls, 2);
+        assert_eq!(scheduler.run(0.0, 10.0, || true), 0);
+    }
+
+    #[test]
+    fn reset_forgets_owed_steps() {
+        let mut scheduler = Scheduler::default();
+        scheduler.run(0.05, 10.0, || true);
+        scheduler.reset();
+        assert_eq!(scheduler.run(0.05, 10.0, || true), 0);
+    }
+}
@@ -0,0 +1,27 @@
+//! Rectangular selection of cells
+
+use super::camera::Camera;
+use super::positions::{GridPosition, GridRect};
+
+use graphics::{Context, Rectangle};
+use opengl_graphics::GlGraphics;
+
+/// Rectangle having both cells as corners, clipped to a grid of the given shape
+pub fn rect_from_corners(a: &GridPosition, b: &GridPosition, (rows, cols): (usize, usize)) -> GridRect {
+    let x = a.0.min(b.0).min(cols.saturating_sub(1));
+    let y = a.1.min(b.1).min(rows.saturating_sub(1));
+    let right = a.0.max(b.0).min(cols.saturating_sub(1));
+    let bottom = a.1.max(b.1).min(rows.saturating_sub(1));
+    GridRect { x, y, width: right - x + 1, height: bottom - y + 1 }
+}
+
+pub fn contains(rect: &GridRect, position: &GridPosition) -> bool {
+    (rect.x..rect.x + rect.width).contains(&position.0) && (rect.y..rect.y + rect.height).contains(&position.1)
+}
+
+/// Outline of the rectangle, drawn through the camera
+pub fn render_outline(rect: &GridRect, camera: &Camera, color: [f32; 4], context: &Context, gl: &mut GlGraphics) {
+    let [x, y] = camera.project([rect.x as f64, rect.y as f64]);
+    let [right, bottom] = camera.project([(rect.x + rect.width) as f64, (rect.y + rect.height) as f64]);
+    Rectangle::new_border(color, 1.0).draw([x, y, right - x, bottom - y], &context.draw_state, context.transform, gl);
+}
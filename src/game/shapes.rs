@@ -0,0 +1,154 @@
+//! Cells covered by geometric shapes, in grid coordinates `[x, y]`
+//!
+//! Coordinates may be outside the grid, it is up to the caller to skip them.
+
+use super::grid::GameGrid;
+
+/// Bresenham line, both ends included
+pub fn line(from: [isize; 2], to: [isize; 2]) -> Vec<[isize; 2]> {
+    let dx = (to[0] - from[0]).abs();
+    let dy = -(to[1] - from[1]).abs();
+    let step_x = if from[0] < to[0] {1} else {-1};
+    let step_y = if from[1] < to[1] {1} else {-1};
+    let mut error = dx + dy;
+    let [mut x, mut y] = from;
+    let mut cells = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        cells.push([x, y]);
+        if [x, y] == to {
+            return cells;
+        }
+        let double = 2 * error;
+        if double >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// End of a line from `from` towards `to`, snapped to a multiple of 45°
+pub fn snap_45(from: [isize; 2], to: [isize; 2]) -> [isize; 2] {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let tan_22_5 = std::f64::consts::SQRT_2 - 1.0;
+    if (dy.abs() as f64) < dx.abs() as f64 * tan_22_5 {
+        [to[0], from[1]]
+    }
+    else if (dx.abs() as f64) < dy.abs() as f64 * tan_22_5 {
+        [from[0], to[1]]
+    }
+    else {
+        let length = dx.abs().max(dy.abs());
+        [from[0] + dx.signum() * length, from[1] + dy.signum() * length]
+    }
+}
+
+/// Corner opposite to `from` of the square dragged towards `to`
+pub fn square_corner(from: [isize; 2], to: [isize; 2]) -> [isize; 2] {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let side = dx.abs().max(dy.abs());
+    [from[0] + if dx < 0 {-side} else {side}, from[1] + if dy < 0 {-side} else {side}]
+}
+
+/// Both ends and the cells in between, in increasing order
+fn span(a: isize, b: isize) -> std::ops::RangeInclusive<isize> {
+    a.min(b)..=a.max(b)
+}
+
+/// Rectangle having both cells as corners
+pub fn rectangle(a: [isize; 2], b: [isize; 2], filled: bool) -> Vec<[isize; 2]> {
+    let on_border = |[x, y]: &[isize; 2]| filled || *x == a[0] || *x == b[0] || *y == a[1] || *y == b[1];
+    span(a[1], b[1])
+        .flat_map(|y| span(a[0], b[0]).map(move |x| [x, y]))
+        .filter(on_border)
+        .collect()
+}
+
+/// Ellipse inscribed in the rectangle having both cells as corners
+pub fn ellipse(a: [isize; 2], b: [isize; 2], filled: bool) -> Vec<[isize; 2]> {
+    let center = [(a[0] + b[0]) as f64 / 2.0, (a[1] + b[1]) as f64 / 2.0];
+    let radius = [(a[0] - b[0]).abs() as f64 / 2.0 + 0.5, (a[1] - b[1]).abs() as f64 / 2.0 + 0.5];
+    let inside = |x: isize, y: isize| {
+        let nx = (x as f64 - center[0]) / radius[0];
+        let ny = (y as f64 - center[1]) / radius[1];
+        nx * nx + ny * ny <= 1.0
+    };
+    span(a[1], b[1])
+        .flat_map(|y| span(a[0], b[0]).map(move |x| [x, y]))
+        .filter(|&[x, y]| inside(x, y)
+            && (filled || !inside(x - 1, y) || !inside(x + 1, y) || !inside(x, y - 1) || !inside(x, y + 1)))
+        .collect()
+}
+
+/// Region of the cells in the same state as `start` and connected to it by their sides
+pub fn flood_fill(grid: &GameGrid, start: [isize; 2]) -> Vec<[isize; 2]> {
+    let state_at = |[x, y]: [isize; 2]| if x < 0 || y < 0 {None} else {grid.get(y as usize, x as usize)};
+    let Some(state) = state_at(start) else {
+        return Vec::new();
+    };
+    let (rows, cols) = grid.get_shape();
+    let mut visited = vec![false; rows * cols];
+    visited[start[1] as usize * cols + start[0] as usize] = true;
+    let mut region = Vec::new();
+    let mut pending = vec![start];
+    while let Some([x, y]) = pending.pop() {
+        region.push([x, y]);
+        for neighbor in [[x - 1, y], [x + 1, y], [x, y - 1], [x, y + 1]] {
+            if state_at(neighbor) == Some(state) {
+                let index = neighbor[1] as usize * cols + neighbor[0] as usize;
+                if !visited[index] {
+                    visited[index] = true;
+                    pending.push(neighbor);
+                }
+            }
+        }
+    }
+    region
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_includes_both_ends() {
+        assert_eq!(line([0, 0], [3, 0]), vec![[0, 0], [1, 0], [2, 0], [3, 0]]);
+        assert_eq!(line([2, 2], [0, 0]), vec![[2, 2], [1, 1], [0, 0]]);
+        let cells = line([0, 0], [5, 2]);
+        assert_eq!((cells.first(), cells.last(), cells.len()), (Some(&[0, 0]), Some(&[5, 2]), 6));
+    }
+
+    #[test]
+    fn snaps_to_45_degrees() {
+        assert_eq!(snap_45([0, 0], [10, 1]), [10, 0]);
+        assert_eq!(snap_45([0, 0], [1, -10]), [0, -10]);
+        assert_eq!(snap_45([0, 0], [-6, 5]), [-6, 6]);
+        assert_eq!(square_corner([0, 0], [-2, 5]), [-5, 5]);
+    }
+
+    #[test]
+    fn rectangles_and_ellipses() {
+        assert_eq!(rectangle([0, 0], [3, 2], true).len(), 12);
+        assert_eq!(rectangle([3, 2], [0, 0], false).len(), 10);
+        let filled = ellipse([0, 0], [6, 6], true);
+        let hollow = ellipse([0, 0], [6, 6], false);
+        assert!(filled.contains(&[3, 3]) && !hollow.contains(&[3, 3]));
+        assert!(hollow.iter().all(|cell| filled.contains(cell)));
+        assert!(!filled.contains(&[0, 0]));
+    }
+
+    #[test]
+    fn flood_fill_stops_at_other_states() {
+        let mut grid = GameGrid::new(5, 5);
+        for [x, y] in rectangle([1, 1], [3, 3], false) {
+            grid.set_state_at(y as usize, x as usize, true).unwrap();
+        }
+        assert_eq!(flood_fill(&grid, [2, 2]), vec![[2, 2]]);
+        assert_eq!(flood_fill(&grid, [1, 1]).len(), 8);
+        assert_eq!(flood_fill(&grid, [0, 0]).len(), 16);
+        assert!(flood_fill(&grid, [-1, 0]).is_empty());
+    }
+}
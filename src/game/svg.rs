@@ -0,0 +1,91 @@
+//! Vector export of what a camera sees
+//!
+//! Alive cells are merged into horizontal runs, one `<rect>` per run.
+
+use super::camera::Camera;
+use super::grid::GameGrid;
+use super::lod;
+use super::positions::GridPosition;
+use super::theme::{hex_color, Theme};
+
+use std::fmt::Write;
+
+/// Labels are put on the coordinates that are multiples of it
+const LABEL_STEP: usize = 10;
+
+#[derive(Clone, Copy, Default)]
+pub struct SvgOptions {
+    pub grid_lines: bool,
+    pub labels: bool,
+}
+
+pub fn render_svg(board: &GameGrid, camera: &Camera, theme: &Theme, options: SvgOptions) -> String {
+    let [width, height] = camera.draw_size();
+    let cell_length = camera.cell_lenght();
+    let visible = camera.visible_cells();
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#, w = width, h = height);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex_color(theme.background));
+
+    let _ = writeln!(svg, r#"<g fill="{}" shape-rendering="crispEdges">"#, hex_color(theme.cell));
+    // Blocks with the density as opacity when cells are smaller than a pixel, like the window does
+    let block = lod::block_size(cell_length);
+    if block > 1 {
+        for ([x, y, width, height], alpha) in lod::visible_blocks(board, camera, block) {
+            let _ = writeln!(svg, r#"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}" fill-opacity="{:.3}"/>"#,
+                x, y, width, height, alpha);
+        }
+    }
+    else {
+        for y in visible.y..visible.y + visible.height {
+            let mut x = visible.x;
+            while x < visible.x + visible.width {
+                if !board.get(y, x).unwrap_or(false) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < visible.x + visible.width && board.get(y, x).unwrap_or(false) {
+                    x += 1;
+                }
+                if let Some([screen_x, screen_y]) = GridPosition(start, y).to_screen_position(camera) {
+                    let _ = writeln!(svg, r#"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}"/>"#,
+                        screen_x, screen_y, (x - start) as f64 * cell_length, cell_length);
+                }
+            }
+        }
+    }
+    let _ = writeln!(svg, "</g>");
+
+    if options.grid_lines {
+        let mut path = String::new();
+        for x in visible.x..=visible.x + visible.width {
+            let [screen_x, _] = camera.project([x as f64, 0.0]);
+            let _ = write!(path, "M{:.3} 0V{} ", screen_x, height);
+        }
+        for y in visible.y..=visible.y + visible.height {
+            let [_, screen_y] = camera.project([0.0, y as f64]);
+            let _ = write!(path, "M0 {:.3}H{} ", screen_y, width);
+        }
+        let _ = writeln!(svg, r#"<path d="{}" stroke="{}" stroke-opacity="0.25" stroke-width="1" fill="none"/>"#,
+            path.trim_end(), hex_color(theme.cell));
+    }
+
+    if options.labels {
+        let font_size = (cell_length * 0.8).clamp(6.0, 14.0);
+        let _ = writeln!(svg, r#"<g fill="{}" font-family="monospace" font-size="{:.1}">"#, hex_color(theme.cell), font_size);
+        for x in (visible.x..visible.x + visible.width).filter(|x| x % LABEL_STEP == 0) {
+            let [screen_x, _] = camera.project([x as f64, 0.0]);
+            let _ = writeln!(svg, r#"<text x="{:.3}" y="{:.1}">{}</text>"#, screen_x + 2.0, font_size, x);
+        }
+        for y in (visible.y..visible.y + visible.height).filter(|y| y % LABEL_STEP == 0) {
+            let [_, screen_y] = camera.project([0.0, y as f64]);
+            let _ = writeln!(svg, r#"<text x="2" y="{:.3}">{}</text>"#, screen_y + font_size, y);
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
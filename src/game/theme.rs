@@ -0,0 +1,67 @@
+//! Colors used to draw the board
+
+use crate::consts::{BG_COLOR, CELL_COLOR};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: [f32; 4],
+    pub cell: [f32; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0].1
+    }
+}
+
+/// Themes that can be cycled through, the first one is the default
+pub const THEMES: [(&str, Theme); 3] = [
+    ("dark", Theme { background: BG_COLOR, cell: CELL_COLOR }),
+    ("light", Theme { background: CELL_COLOR, cell: BG_COLOR }),
+    ("matrix", Theme { background: [0.0, 0.05, 0.0, 1.0], cell: [0.2, 0.95, 0.3, 1.0] }),
+];
+
+/// Channels from 0 to 255
+pub fn to_rgba8(color: [f32; 4]) -> [u8; 4] {
+    color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// `#rrggbb`, the alpha is ignored
+pub fn hex_color(color: [f32; 4]) -> String {
+    let [r, g, b, _] = to_rgba8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn parse_hex_color(value: &str) -> Option<[f32; 4]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|c| c as f32 / 255.0);
+    Some([channel(0)?, channel(2)?, channel(4)?, 1.0])
+}
+
+impl Theme {
+    /// Either the name of one of the `THEMES` or `<BACKGROUND>,<CELL>` as hexadecimal RGB
+    ///
+    /// ## Errors
+    /// If the name is unknown or a color is invalid
+    pub fn parse(value: &str) -> Result<Theme, String> {
+        if let Some((_, theme)) = THEMES.iter().find(|(name, _)| *name == value) {
+            return Ok(*theme);
+        }
+        let invalid = || format!("Invalid palette {:?}, expected a theme name or <BACKGROUND>,<CELL> like 141414,f2f2f2", value);
+        let (background, cell) = value.split_once(',').ok_or_else(invalid)?;
+        Ok(Theme {
+            background: parse_hex_color(background).ok_or_else(invalid)?,
+            cell: parse_hex_color(cell).ok_or_else(invalid)?,
+        })
+    }
+
+    /// RGB of the background then of the cells
+    pub fn to_rgb8_palette(self) -> [u8; 6] {
+        let [r, g, b, _] = to_rgba8(self.background);
+        let [cr, cg, cb, _] = to_rgba8(self.cell);
+        [r, g, b, cr, cg, cb]
+    }
+}
@@ -0,0 +1,224 @@
+//! Generations of the current run, to scrub back and forth through time
+//!
+//! A keyframe is kept every `KEYFRAME_INTERVAL` generations, the generations
+//! in between are recomputed from the keyframe before them. The oldest
+//! keyframes are evicted when the timeline is larger than its memory budget.
+
+use super::grid::GameGrid;
+use super::rle;
+use super::theme::Theme;
+use crate::consts::{KEYFRAME_INTERVAL, SCRUBBER_HEIGHT};
+
+use graphics::{rectangle, Context};
+use opengl_graphics::GlGraphics;
+use std::collections::BTreeMap;
+
+pub struct Timeline {
+    /// Generation of the board
+    generation: u64,
+    /// Last generation computed since the board was edited
+    latest: u64,
+    /// RLE snapshots, by generation
+    keyframes: BTreeMap<u64, String>,
+    /// In bytes
+    budget: usize,
+    size: usize,
+    /// What happened to the last requested generation, if it was not simply found
+    status: Option<String>,
+}
+
+impl Timeline {
+    pub fn new(board: &GameGrid, budget: usize) -> Self {
+        let mut timeline = Timeline {
+            generation: 0,
+            latest: 0,
+            keyframes: BTreeMap::new(),
+            budget,
+            size: 0,
+            status: None,
+        };
+        timeline.insert_keyframe(0, rle::encode(board));
+        timeline
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// The board went to the next generation
+    pub fn advance(&mut self, board: &GameGrid) {
+        self.advance_to(self.generation + 1);
+        if self.generation.is_multiple_of(KEYFRAME_INTERVAL) {
+            self.insert_keyframe(self.generation, rle::encode(board));
+        }
+    }
+
+    /// The board went to a later generation computed elsewhere, that gives
+    /// the keyframes to `add_keyframe`
+    pub fn advance_to(&mut self, generation: u64) {
+        self.generation = generation;
+        self.latest = self.latest.max(self.generation);
+        self.status = None;
+    }
+
+    /// RLE snapshot of a generation computed elsewhere
+    pub fn add_keyframe(&mut self, generation: u64, snapshot: String) {
+        self.latest = self.latest.max(generation);
+        self.insert_keyframe(generation, snapshot);
+    }
+
+    /// The board was changed by hand, its future is unknown again
+    pub fn rewrite(&mut self, board: &GameGrid) {
+        self.rewrite_at(self.generation, rle::encode(board));
+    }
+
+    /// Like `rewrite`, for a board changed elsewhere at the given generation
+    pub fn rewrite_at(&mut self, generation: u64, snapshot: String) {
+        self.size -= self.keyframes.split_off(&generation).values().map(String::len).sum::<usize>();
+        self.generation = generation;
+        self.latest = generation;
+        self.status = None;
+        self.insert_keyframe(generation, snapshot);
+    }
+
+    /// The board was put back as it was at the given generation, the keyframes
+    /// are kept as long as they still lead to it
+    pub fn restore(&mut self, generation: u64, board: &GameGrid) {
+        if self.seek(generation) != *board || self.generation != generation {
+            self.generation = generation;
+            self.rewrite(board);
+        }
+        self.status = None;
+    }
+
+    /// Board at the given generation, at most the latest one
+    ///
+    /// An evicted generation is replaced by the earliest one that is kept.
+    pub fn seek(&mut self, generation: u64) -> GameGrid {
+        let generation = generation.min(self.latest);
+        let (&keyframe, snapshot) = self.keyframes.range(..=generation).next_back()
+            .or_else(|| self.keyframes.first_key_value())
+            .expect("The timeline always keeps a keyframe");
+        let mut board = rle::decode(snapshot).expect("Keyframes are valid RLE");
+
+        let target = generation.max(keyframe);
+        for _ in keyframe..target {
+            board.next_generation();
+        }
+        self.status = if target != generation {
+            Some(format!("Generation {} was evicted, back to {}", generation, target))
+        }
+        else if target != keyframe {
+            Some(format!("Generation {} recomputed from keyframe {}", target, keyframe))
+        }
+        else {
+            None
+        };
+        self.generation = target;
+        board
+    }
+
+    fn insert_keyframe(&mut self, generation: u64, snapshot: String) {
+        self.size += snapshot.len();
+        if let Some(previous) = self.keyframes.insert(generation, snapshot) {
+            self.size -= previous.len();
+        }
+        while self.size > self.budget && self.keyframes.len() > 1 {
+            let (_, evicted) = self.keyframes.pop_first().unwrap();
+            self.size -= evicted.len();
+        }
+    }
+
+    /// Generation under the cursor when it is on the scrubber bar
+    pub fn generation_at(&self, position: [f64; 2], draw_size: [f64; 2]) -> Option<u64> {
+        if position[1] < draw_size[1] - SCRUBBER_HEIGHT || draw_size[0] <= 0.0 {
+            return None;
+        }
+        let ratio = (position[0] / draw_size[0]).clamp(0.0, 1.0);
+        Some((ratio * self.latest as f64).round() as u64)
+    }
+
+    /// Bar at the bottom of the screen, evicted generations are left empty
+    pub fn render_scrubber(&self, theme: &Theme, draw_size: [f64; 2], context: &Context, gl: &mut GlGraphics) {
+        let [r, g, b, _] = theme.cell;
+        let y = draw_size[1] - SCRUBBER_HEIGHT;
+        let to_x = |generation: u64| if self.latest == 0 {draw_size[0]} else {generation as f64 / self.latest as f64 * draw_size[0]};
+
+        let earliest = self.keyframes.keys().next().copied().unwrap_or(0);
+        rectangle([r, g, b, 0.15], [to_x(earliest), y, draw_size[0] - to_x(earliest), SCRUBBER_HEIGHT], context.transform, gl);
+        rectangle([r, g, b, 0.35], [to_x(earliest), y, to_x(self.generation) - to_x(earliest), SCRUBBER_HEIGHT], context.transform, gl);
+        rectangle([r, g, b, 0.9], [to_x(self.generation) - 1.5, y, 3.0, SCRUBBER_HEIGHT], context.transform, gl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r_pentomino() -> GameGrid {
+        let mut board = GameGrid::new(32, 32);
+        for (row, col) in [(15, 16), (15, 17), (16, 15), (16, 16), (17, 16)] {
+            board.set_state_at(row, col, true).unwrap();
+        }
+        board
+    }
+
+    fn replayed(generations: u64) -> GameGrid {
+        let mut board = r_pentomino();
+        for _ in 0..generations {
+            board.next_generation();
+        }
+        board
+    }
+
+    #[test]
+    fn seek_matches_replayed_board() {
+        let mut board = r_pentomino();
+        let mut timeline = Timeline::new(&board, usize::MAX);
+        for _ in 0..20 {
+            board.next_generation();
+            timeline.advance(&board);
+        }
+        for generation in [0, 7, 8, 13, 20] {
+            assert!(timeline.seek(generation) == replayed(generation));
+            assert_eq!(timeline.generation(), generation);
+        }
+        timeline.seek(16);
+        assert_eq!(timeline.status(), None);
+        timeline.seek(13);
+        assert_eq!(timeline.status(), Some("Generation 13 recomputed from keyframe 8"));
+        // Not computed yet
+        assert!(timeline.seek(30) == replayed(20));
+    }
+
+    #[test]
+    fn rewrite_forgets_the_future() {
+        let mut board = r_pentomino();
+        let mut timeline = Timeline::new(&board, usize::MAX);
+        for _ in 0..20 {
+            board.next_generation();
+            timeline.advance(&board);
+        }
+        let mut edited = timeline.seek(10);
+        edited.clear();
+        timeline.rewrite(&edited);
+        assert!(timeline.seek(20) == edited);
+        assert_eq!(timeline.generation(), 10);
+    }
+
+    #[test]
+    fn evicted_generations_come_back_to_the_earliest_kept() {
+        let mut board = r_pentomino();
+        let mut timeline = Timeline::new(&board, 0);
+        for _ in 0..16 {
+            board.next_generation();
+            timeline.advance(&board);
+        }
+        assert!(timeline.seek(3) == replayed(16));
+        assert_eq!(timeline.generation(), 16);
+    }
+}
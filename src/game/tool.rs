@@ -0,0 +1,30 @@
+/// What a left click does
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    Draw, // Draw or erase the cells under the cursor with the brush
+    Select, // Drag a rectangle, or move the selected cells
+    Line,
+    Rectangle { filled: bool },
+    Ellipse { filled: bool },
+    Fill // Switch the connected cells in the same state as the clicked one
+}
+
+impl Tool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Draw => "Draw",
+            Tool::Select => "Select",
+            Tool::Line => "Line (LShift snaps to 45°)",
+            Tool::Rectangle { filled: false } => "Rectangle (LShift for a square)",
+            Tool::Rectangle { filled: true } => "Filled rectangle (LShift for a square)",
+            Tool::Ellipse { filled: false } => "Ellipse (LShift for a circle)",
+            Tool::Ellipse { filled: true } => "Filled ellipse (LShift for a circle)",
+            Tool::Fill => "Flood fill"
+        }
+    }
+
+    /// Line, rectangle or ellipse, dragged from one corner to the other
+    pub fn is_shape(self) -> bool {
+        matches!(self, Tool::Line | Tool::Rectangle { .. } | Tool::Ellipse { .. })
+    }
+}
@@ -0,0 +1,241 @@
+//! Simulation running on a worker thread while the game plays
+//!
+//! A copy of the board is handed over to the worker when the game plays, and
+//! cells drawn meanwhile are sent to it as edits. The worker publishes
+//! snapshots for the window to render and the RLE of the keyframes for the
+//! timeline, the event loop never waits for a generation. When the game pauses
+//! it keeps the last snapshot shown, what the worker computed since is dropped.
+
+use super::grid::GameGrid;
+use super::rle;
+use super::scheduler::Scheduler;
+use crate::consts::KEYFRAME_INTERVAL;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Snapshots of the steps in between are skipped
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(8);
+
+pub struct Snapshot {
+    pub generation: u64,
+    pub board: GameGrid,
+}
+
+pub enum Update {
+    /// Board to show
+    Show(Snapshot),
+    /// RLE of the board at a multiple of `KEYFRAME_INTERVAL`
+    Keyframe { generation: u64, rle: String },
+    /// The board after an edit and its RLE, the later keyframes are outdated
+    Edited { snapshot: Snapshot, rle: String },
+}
+
+/// Cells set to the same state
+#[derive(Clone)]
+pub struct Edit {
+    /// `(row, col)`, the ones outside the board are ignored
+    pub cells: Vec<(usize, usize)>,
+    pub alive: bool,
+}
+
+impl Edit {
+    pub fn apply(&self, board: &mut GameGrid) {
+        for &(row, col) in &self.cells {
+            let _ = board.set_state_at(row, col, self.alive);
+        }
+    }
+}
+
+enum Command {
+    Play { run: u64, board: GameGrid, generation: u64, rate: f64, step_exponent: u32 },
+    SetRate { rate: f64, step_exponent: u32 },
+    Edit(Edit),
+    Pause,
+}
+
+pub struct Simulation {
+    commands: Sender<Command>,
+    /// Tagged with the run they belong to
+    updates: Receiver<(u64, Update)>,
+    /// Set with each command, the worker checks it between generations
+    interrupt: Arc<AtomicBool>,
+    /// Updates of earlier runs are outdated
+    run: u64,
+    /// Edits sent during the run that the worker has not applied yet, oldest first
+    pending: VecDeque<Edit>,
+}
+
+impl Simulation {
+    pub fn spawn() -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (update_sender, updates) = mpsc::channel();
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let worker_interrupt = Arc::clone(&interrupt);
+        thread::spawn(move || Worker::default().work(&command_receiver, &update_sender, &worker_interrupt));
+        Simulation { commands, updates, interrupt, run: 0, pending: VecDeque::new() }
+    }
+
+    /// Start running from a copy of the board
+    pub fn play(&mut self, board: &GameGrid, generation: u64, rate: f64, step_exponent: u32) {
+        self.run += 1;
+        self.send(Command::Play { run: self.run, board: board.clone(), generation, rate, step_exponent });
+    }
+
+    pub fn set_rate(&self, rate: f64, step_exponent: u32) {
+        self.send(Command::SetRate { rate, step_exponent });
+    }
+
+    /// Change cells of the running board, it is up to the caller to change the board shown
+    pub fn edit(&mut self, edit: Edit) {
+        self.send(Command::Edit(edit.clone()));
+        self.pending.push_back(edit);
+    }
+
+    /// Edits not in the boards published yet, to apply again on them
+    pub fn pending(&self) -> impl Iterator<Item = &Edit> {
+        self.pending.iter()
+    }
+
+    /// Updates published since the last call, oldest first
+    pub fn poll(&mut self) -> Vec<Update> {
+        let updates: Vec<Update> = self.updates.try_iter()
+            .filter(|(run, _)| *run == self.run)
+            .map(|(_, update)| update)
+            .collect();
+        for update in &updates {
+            if let Update::Edited { .. } = update {
+                self.pending.pop_front();
+            }
+        }
+        updates
+    }
+
+    /// Stop running without waiting for the worker, the updates not polled yet are dropped
+    ///
+    /// Return the edits that the worker had not applied yet.
+    pub fn pause(&mut self) -> Vec<Edit> {
+        self.run += 1;
+        self.send(Command::Pause);
+        self.pending.drain(..).collect()
+    }
+
+    fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+        self.interrupt.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Run {
+    id: u64,
+    board: GameGrid,
+    generation: u64,
+}
+
+struct Worker {
+    running: Option<Run>,
+    rate: f64,
+    step_exponent: u32,
+    scheduler: Scheduler,
+    /// Generations of the current step still to run, an interrupted step resumes from them
+    step_left: u64,
+    last_update: Instant,
+    last_publish: Instant,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        Worker {
+            running: None,
+            rate: 0.0,
+            step_exponent: 0,
+            scheduler: Scheduler::default(),
+            step_left: 0,
+            last_update: Instant::now(),
+            last_publish: Instant::now(),
+        }
+    }
+}
+
+impl Worker {
+    fn work(mut self, commands: &Receiver<Command>, updates: &Sender<(u64, Update)>, interrupt: &AtomicBool) {
+        loop {
+            interrupt.store(false, Ordering::Relaxed);
+            // Nothing to do but wait while paused
+            if self.running.is_none() {
+                match commands.recv() {
+                    Ok(command) => self.handle(command, updates),
+                    Err(_) => return // The game is gone
+                }
+            }
+            loop {
+                match commands.try_recv() {
+                    Ok(command) => self.handle(command, updates),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return
+                }
+            }
+
+            let Some(Run { id, board, generation }) = &mut self.running else {
+                continue;
+            };
+            let dt = self.last_update.elapsed().as_secs_f64();
+            self.last_update = Instant::now();
+            let step_exponent = self.step_exponent;
+            let step_left = &mut self.step_left;
+            let steps = self.scheduler.run(dt, self.rate, || {
+                if *step_left == 0 {
+                    *step_left = 1 << step_exponent;
+                }
+                while *step_left > 0 {
+                    // A command is handled without waiting for the end of a long step
+                    if interrupt.load(Ordering::Relaxed) {
+                        return false;
+                    }
+                    board.next_generation();
+                    *generation += 1;
+                    *step_left -= 1;
+                    // Encoded here so that the window does not have to
+                    if generation.is_multiple_of(KEYFRAME_INTERVAL) {
+                        let _ = updates.send((*id, Update::Keyframe { generation: *generation, rle: rle::encode(board) }));
+                    }
+                }
+                true
+            });
+            if steps == 0 && !interrupt.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            else if self.last_publish.elapsed() >= PUBLISH_INTERVAL {
+                self.last_publish = Instant::now();
+                let _ = updates.send((*id, Update::Show(Snapshot { generation: *generation, board: board.clone() })));
+            }
+        }
+    }
+
+    fn handle(&mut self, command: Command, updates: &Sender<(u64, Update)>) {
+        match command {
+            Command::Play { run, board, generation, rate, step_exponent } => {
+                self.running = Some(Run { id: run, board, generation });
+                (self.rate, self.step_exponent) = (rate, step_exponent);
+                self.scheduler.reset();
+                self.step_left = 0;
+                self.last_update = Instant::now();
+            },
+            Command::SetRate { rate, step_exponent } => {
+                (self.rate, self.step_exponent) = (rate, step_exponent);
+            },
+            Command::Edit(edit) => {
+                if let Some(Run { id, board, generation }) = &mut self.running {
+                    edit.apply(board);
+                    let snapshot = Snapshot { generation: *generation, board: board.clone() };
+                    let _ = updates.send((*id, Update::Edited { snapshot, rle: rle::encode(board) }));
+                }
+            },
+            Command::Pause => self.running = None
+        }
+    }
+}
@@ -3,34 +3,87 @@ extern crate glfw_window;
 extern crate piston;
 extern crate graphics;
 
+mod cli;
 mod consts;
 mod game;
 
-use consts::{DEFAULT_WINDOW_SIZE, OPEN_GL};
-use game::Game;
+use cli::{Command, GuiOptions};
+use consts::{DEFAULT_WINDOW_SIZE, OPEN_GL, WINDOW_TITLE};
+use game::{export, Autosave, Game};
 
 use glfw_window::GlfwWindow;
-use piston::{EventSettings, Events, MouseCursorEvent, PressEvent, ReleaseEvent, RenderEvent, ResizeEvent, UpdateEvent, WindowSettings};
+use std::process::ExitCode;
 
+use piston::{AdvancedWindow, Event, EventSettings, Events, FileDrag, Input, MouseCursorEvent, MouseScrollEvent, PressEvent, ReleaseEvent, RenderEvent, ResizeEvent, TextEvent, UpdateEvent, WindowSettings};
 
-fn main() {
-    let mut window: GlfwWindow = WindowSettings::new("Conway's Game Of Life", DEFAULT_WINDOW_SIZE)
+
+fn main() -> ExitCode {
+    let result = match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Gui(options)) => {
+            run_window(&options);
+            Ok(())
+        },
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        },
+        Ok(Command::Screenshot(options)) => export::screenshot(&options),
+        Ok(Command::Gif(options, gif_options)) => export::gif(&options, &gif_options),
+        Ok(Command::Svg(options, svg_options, region)) => export::svg(&options, svg_options, region),
+        Err(error) => Err(format!("{}\n\n{}", error, cli::USAGE))
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_window(options: &GuiOptions) {
+    let autosave = Autosave::open();
+    let crashed = autosave.previous_session_crashed();
+
+    let mut window: GlfwWindow = WindowSettings::new(WINDOW_TITLE, DEFAULT_WINDOW_SIZE)
         .graphics_api(OPEN_GL)
-        .exit_on_esc(true)
+        .exit_on_esc(false) // Too easy to hit by accident
         .automatic_close(true)
         .resizable(true)
         .build()
         .expect("Could not create window");
 
-    let mut game = Game::new();
+    let mut game = Game::new(autosave);
+    if crashed {
+        game.offer_restore(); // In the window, there may be no terminal
+    }
+    game.set_import_options(options.image);
+    game.set_fill_density(options.fill_density);
+    game.set_history_budget(options.history_budget);
+    game.set_step_count(options.step_count);
+    game.set_inertia(options.inertia);
+    if let Some(dir) = &options.pattern_dir {
+        game.watch_patterns(dir.clone());
+    }
+    if let Some(pattern) = &options.pattern {
+        game.load_file(pattern);
+    }
+    game.begin_session();
     let mut event_manager = Events::new(EventSettings::new());
 
+    let mut title = String::new();
     while let Some(event) = event_manager.next(&mut window) {
         if let Some(resize_arg) = event.resize_args() { // When new != last
             game.resize(&resize_arg);
         }
         if let Some(render_arg) = event.render_args() { // Every tick
             game.render(&render_arg);
+            let new_title = game.title();
+            if new_title != title {
+                window.set_title(new_title.clone());
+                title = new_title;
+            }
         }
         if let Some(update_arg) = event.update_args() { // Every tick
             game.update(&update_arg);
@@ -38,11 +91,22 @@ fn main() {
         if let Some(position) = event.mouse_cursor_args() { // When new mouse_pos != last_mouse_pos
             game.update_mouse_position(position);
         }
+        if let Some([_, scroll]) = event.mouse_scroll_args() { // When the wheel turns
+            game.handle_scroll(scroll);
+        }
         if let Some(pressed_button) = event.press_args() { // When starting to press (1 tick)
             game.handle_button_press(pressed_button);
         }
         if let Some(released_button) = event.release_args() { // When stopping (1 tick)
             game.handle_button_release(released_button);
         }
+        if let Some(text) = event.text_args() { // When characters are typed
+            game.handle_text(&text);
+        }
+        if let Event::Input(Input::FileDrag(FileDrag::Drop(path)), _) = &event { // When a file is dropped on the window
+            game.load_file(path);
+        }
     }
+
+    game.end_session();
 }
\ No newline at end of file